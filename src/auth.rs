@@ -34,7 +34,7 @@ pub struct XboxLiveData {
 // Login process
 
 pub async fn request_code() -> AuthCode {
-    let client = Client::new();
+    let client = super::network::client();
     let response = match client
         .get("https://login.microsoftonline.com/consumers/oauth2/v2.0/devicecode")
         .query(&[
@@ -84,7 +84,7 @@ pub fn start_wait_for_login<I: 'static + Hash + Copy + Send + Sync>(
 ) -> iced::Subscription<(I, WaitProgress)> {
     subscription::unfold(
         id,
-        WaitState::Waiting(Client::new(), device_code),
+        WaitState::Waiting(super::network::client(), device_code),
         move |state| wait_for_login(id, state),
     )
 }
@@ -138,7 +138,7 @@ pub async fn wait_for_login<Id: Copy>(id: Id, state: WaitState) -> ((Id, WaitPro
 }
 
 pub async fn login_to_xbox(access_token: String) -> XboxLiveData {
-    let client = Client::new();
+    let client = super::network::client();
 
     // Xbox live
     let xbox_live_response_request_data = json!({
@@ -212,7 +212,7 @@ pub async fn login_to_xbox(access_token: String) -> XboxLiveData {
 }
 
 pub async fn login_to_minecraft(xbox_data: XboxLiveData) -> MinecraftAccount {
-    let client = Client::new();
+    let client = super::network::client();
 
     // Getting token
     let minecraft_data_response_request_data = json!(
@@ -263,7 +263,7 @@ pub async fn login_to_minecraft(xbox_data: XboxLiveData) -> MinecraftAccount {
 
 // All in one, using a refresh_token. Used when launching the game.
 pub async fn login_with_refresh_token(refresh_token: String) -> Option<MinecraftAccount> {
-    let client = Client::new();
+    let client = super::network::client();
 
     let response = match client
         .post("https://login.microsoftonline.com/consumers/oauth2/v2.0/token")
@@ -1,29 +1,62 @@
-use iced::subscription;
-use reqwest::{self, Client};
+use iced::{
+    futures::{
+        future::{self, BoxFuture},
+        stream::FuturesUnordered,
+        StreamExt,
+    },
+    subscription,
+};
+use reqwest::{
+    self,
+    header::{HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RANGE},
+    Client, StatusCode,
+};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
 use std::{
+    collections::HashSet,
     env,
     fs::{self, File},
     hash::Hash,
     io::{BufReader, Read, Write},
-    path::Path,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
 };
 use zip::ZipArchive;
 
+use super::forge::{ForgeInstall, ForgeLoader};
+
 pub enum State {
-    GettingDownloadList(String, VersionType),
-    Downloading(DownloadList),
-    PreparingJavaDownload(Java),
+    GettingDownloadList(String, VersionType, DownloadSettings),
+    Downloading(ParallelDownload, Option<ForgeInstall>),
+    RunningForgeProcessors(ForgeInstall),
+    // java, attempt
+    PreparingJavaDownload(u32, u32),
     DownloadingJava {
         downloaded: u64,
         total: u64,
         download: reqwest::Response,
         folder_to_store: String,
         file_to_write: File,
-        java: Java,
+        java: u32,
+        checksum: String,
+        hasher: Sha256,
+        attempt: u32,
     },
-    ExtractingJava(String, Java),
-    DownloadingMissingFiles(DownloadList),
+    ExtractingJava(String, u32),
+    PreparingJavaRuntime(String),
+    DownloadingJavaRuntime(JavaRuntime),
+    DownloadingMissingFiles(ParallelDownload),
+    // version, concurrency
+    PreparingRepair(String, usize),
+    VerifyingFiles(Verification),
+    RepairingFiles(ParallelDownload, RepairSummary),
     PreparingUpdate(String),
     DownloadingUpdate {
         downloaded: u64,
@@ -36,22 +69,96 @@ pub enum State {
 #[derive(Debug, Clone, PartialEq)]
 pub enum VersionType {
     Vanilla,
-    Fabric,
+    // the loader version, e.g. 0.15.11
+    Fabric(String),
+    Quilt(String),
+    // the forge version without the game version, e.g. 49.0.30
+    Forge(String),
+    NeoForge(String),
+}
+
+impl VersionType {
+    // the loader version is part of the name, so different loaders can be installed together
+    pub fn get_version_name(&self, version: &str) -> String {
+        match self {
+            VersionType::Vanilla => version.to_owned(),
+            VersionType::Fabric(loader) => format!("{}-fabric-{}", version, loader),
+            VersionType::Quilt(loader) => format!("{}-quilt-{}", version, loader),
+            VersionType::Forge(forge) => format!("{}-forge-{}", version, forge),
+            VersionType::NeoForge(neoforge) => format!("{}-neoforge-{}", version, neoforge),
+        }
+    }
+
+    // Url of the loader json that inherits from the vanilla one.
+    fn get_profile_url(&self, version: &str) -> Option<String> {
+        match self {
+            VersionType::Vanilla | VersionType::Forge(_) | VersionType::NeoForge(_) => None,
+            VersionType::Fabric(loader) => Some(format!(
+                "https://meta.fabricmc.net/v2/versions/loader/{}/{}/profile/json",
+                version, loader
+            )),
+            VersionType::Quilt(loader) => Some(format!(
+                "https://meta.quiltmc.org/v3/versions/loader/{}/{}/profile/json",
+                version, loader
+            )),
+        }
+    }
+
+    // Url of the installer that makes the loader json.
+    fn get_installer_url(&self, version: &str) -> Option<String> {
+        match self {
+            VersionType::Forge(forge) => Some(ForgeLoader::Forge.get_installer_url(version, forge)),
+            VersionType::NeoForge(neoforge) => {
+                Some(ForgeLoader::NeoForge.get_installer_url(version, neoforge))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DownloadSettings {
+    pub concurrency: usize,
+    // runs the forge installer processors
+    pub java: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoaderVersion {
+    pub version: String,
+    pub stable: bool,
+}
+
+impl std::fmt::Display for LoaderVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.stable {
+            true => write!(f, "{}", self.version),
+            false => write!(f, "{} (unstable)", self.version),
+        }
+    }
 }
 #[derive(Debug, Clone, PartialEq)]
 pub enum Progress {
     GotDownloadList(i32),
-    Downloaded(i32),
+    Downloaded(DownloadProgress),
+    RunningForgeProcessors,
     Finished,
 
-    StartedJavaDownload(u8),
-    JavaDownloadProgressed(u8, u8),
+    StartedJavaDownload(u16),
+    JavaDownloadProgressed(u16, u8),
+    JavaDownloadRetrying(String),
     JavaDownloadFinished,
     JavaExtracted,
 
-    MissingFilesDownloadProgressed(u16),
+    MissingFilesDownloadProgressed(DownloadProgress),
     MissingFilesDownloadFinished,
 
+    // checked, total
+    RepairVerifying(usize, usize),
+    RepairDownloadProgressed(DownloadProgress),
+    RepairFinished(RepairSummary),
+    RepairErrored(String),
+
     UpdateStarted(u8),
     UpdateProgressed(u8, u8, u8),
     UpdateFinished,
@@ -63,37 +170,56 @@ pub fn start<I: 'static + Hash + Copy + Send + Sync>(
     id: I,
     version: String,
     version_type: VersionType,
+    settings: DownloadSettings,
 ) -> iced::Subscription<(I, Progress)> {
     subscription::unfold(
         id,
-        State::GettingDownloadList(version, version_type),
+        State::GettingDownloadList(version, version_type, settings),
         move |state| download(id, state),
     )
 }
-#[derive(Clone)]
-pub enum Java {
-    J8,
-    J17,
-    J21,
-}
+// java is the major version, e.g. 17
 pub fn start_java<I: 'static + Hash + Copy + Send + Sync>(
     id: I,
-    java: Java,
+    java: u32,
 ) -> iced::Subscription<(I, Progress)> {
-    subscription::unfold(id, State::PreparingJavaDownload(java), move |state| {
+    subscription::unfold(id, State::PreparingJavaDownload(java, 1), move |state| {
         download(id, state)
     })
 }
 
-pub fn start_missing_files<I: 'static + Hash + Copy + Send + Sync>(
+pub fn start_java_runtime<I: 'static + Hash + Copy + Send + Sync>(
     id: I,
-    files: DownloadList,
+    component: String,
 ) -> iced::Subscription<(I, Progress)> {
-    subscription::unfold(id, State::DownloadingMissingFiles(files), move |state| {
+    subscription::unfold(id, State::PreparingJavaRuntime(component), move |state| {
         download(id, state)
     })
 }
 
+pub fn start_missing_files<I: 'static + Hash + Copy + Send + Sync>(
+    id: I,
+    files: DownloadList,
+) -> iced::Subscription<(I, Progress)> {
+    subscription::unfold(
+        id,
+        State::DownloadingMissingFiles(ParallelDownload::new(files)),
+        move |state| download(id, state),
+    )
+}
+
+pub fn start_repair<I: 'static + Hash + Copy + Send + Sync>(
+    id: I,
+    version: String,
+    concurrency: usize,
+) -> iced::Subscription<(I, Progress)> {
+    subscription::unfold(
+        id,
+        State::PreparingRepair(version, concurrency),
+        move |state| download(id, state),
+    )
+}
+
 pub fn start_update<I: 'static + Hash + Copy + Send + Sync>(
     id: I,
     url: String,
@@ -106,11 +232,843 @@ pub fn start_update<I: 'static + Hash + Copy + Send + Sync>(
 pub struct DownloadList {
     pub download_list: Vec<Download>,
     pub client: Client,
+    pub concurrency: usize,
+}
+
+// Bytes are counted from the sizes in the version json, files without a size are added once they finish.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DownloadProgress {
+    pub files_left: usize,
+    pub downloaded: u64,
+    pub total: u64,
+    pub files: Vec<FileProgress>,
+}
+
+impl DownloadProgress {
+    pub fn get_percentage(&self) -> u8 {
+        match self.total {
+            0 => 0,
+            total => (self.downloaded.min(total) * 100 / total) as u8,
+        }
+    }
+}
+
+// e.g. "12.3 / 450.0 MiB (2%), client.jar 3.1 / 25.4 MiB", only the biggest running file is shown.
+impl std::fmt::Display for DownloadProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.1} / {:.1} MiB ({}%)",
+            self.downloaded as f64 / 1048576.,
+            self.total as f64 / 1048576.,
+            self.get_percentage()
+        )?;
+
+        let biggest_file = self
+            .files
+            .iter()
+            .filter_map(|file| Some((file, file.size?)))
+            .filter(|(_, size)| *size >= 1048576)
+            .max_by_key(|(_, size)| *size);
+        if let Some((file, size)) = biggest_file {
+            write!(
+                f,
+                ", {} {:.1} / {:.1} MiB",
+                file.name,
+                file.downloaded as f64 / 1048576.,
+                size as f64 / 1048576.
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// A file that is being downloaded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileProgress {
+    pub name: String,
+    pub downloaded: u64,
+    pub size: Option<u64>,
+}
+
+struct RunningFile {
+    path: String,
+    size: Option<u64>,
+    downloaded: Arc<AtomicU64>,
+}
+
+// How often the byte progress is sent while no file finishes.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+// Downloads up to `concurrency` files at the same time over the same client.
+pub struct ParallelDownload {
+    pending: Vec<Download>,
+    running: FuturesUnordered<BoxFuture<'static, (usize, Result<(), String>)>>,
+    running_files: Vec<(usize, RunningFile)>,
+    next_id: usize,
+    finished_bytes: u64,
+    total: u64,
+    client: Client,
+    concurrency: usize,
+}
+
+impl ParallelDownload {
+    fn new(download_list: DownloadList) -> Self {
+        let mut pending = download_list.download_list;
+        // assets with the same hash share a path, two downloads can't write the same .part
+        let mut paths = HashSet::new();
+        pending.retain(|file| paths.insert(file.path.clone()));
+        // files are taken from the end
+        pending.reverse();
+        let total = pending.iter().filter_map(|file| file.size).sum();
+
+        ParallelDownload {
+            pending,
+            running: FuturesUnordered::new(),
+            running_files: vec![],
+            next_id: 0,
+            finished_bytes: 0,
+            total,
+            client: download_list.client,
+            concurrency: download_list.concurrency.max(1),
+        }
+    }
+
+    fn get_progress(&self) -> DownloadProgress {
+        let files: Vec<FileProgress> = self
+            .running_files
+            .iter()
+            .map(|(_, file)| FileProgress {
+                name: get_file_name(&file.path),
+                downloaded: file.downloaded.load(Ordering::Relaxed),
+                size: file.size,
+            })
+            .collect();
+
+        DownloadProgress {
+            files_left: self.pending.len() + self.running.len(),
+            downloaded: self.finished_bytes + files.iter().map(|file| file.downloaded).sum::<u64>(),
+            total: self.total,
+            files,
+        }
+    }
+
+    // Waits for the next file or PROGRESS_INTERVAL, returns the progress or None when everything is downloaded.
+    async fn next(&mut self) -> Option<Result<DownloadProgress, String>> {
+        while self.running.len() < self.concurrency {
+            let Some(file) = self.pending.pop() else {
+                break;
+            };
+
+            let id = self.next_id;
+            self.next_id += 1;
+            let downloaded = Arc::new(AtomicU64::new(0));
+            self.running_files.push((
+                id,
+                RunningFile {
+                    path: file.path.clone(),
+                    size: file.size,
+                    downloaded: downloaded.clone(),
+                },
+            ));
+
+            let client = self.client.clone();
+            self.running.push(Box::pin(async move {
+                (id, download_file(client, file, downloaded).await)
+            }));
+        }
+
+        if self.running.is_empty() {
+            return None;
+        }
+
+        let timer = Box::pin(tokio::time::sleep(PROGRESS_INTERVAL));
+        if let future::Either::Left((Some((id, result)), _)) =
+            future::select(self.running.next(), timer).await
+        {
+            if let Err(e) = result {
+                return Some(Err(e));
+            }
+
+            if let Some(index) = self
+                .running_files
+                .iter()
+                .position(|(file_id, _)| *file_id == id)
+            {
+                let (_, file) = self.running_files.remove(index);
+                let downloaded = file.downloaded.load(Ordering::Relaxed);
+                self.finished_bytes += file.size.unwrap_or(downloaded);
+                if file.size.is_none() {
+                    self.total += downloaded;
+                }
+            }
+        }
+
+        Some(Ok(self.get_progress()))
+    }
+}
+
+fn get_file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_owned())
+}
+
+const MAX_DOWNLOAD_ATTEMPTS: u32 = 5;
+
+// Waits 1, 2, 4, 8... seconds before trying again.
+async fn backoff(attempt: u32) {
+    tokio::time::sleep(Duration::from_secs(1 << (attempt - 1).min(5))).await
+}
+
+// `downloaded` counts the bytes of the file, including a resumed .part.
+async fn download_file(
+    client: Client,
+    file: Download,
+    downloaded: Arc<AtomicU64>,
+) -> Result<(), String> {
+    println!("Downloading {}", file.path);
+
+    if let Some(dir) = Path::new(&file.path).parent() {
+        if let Err(e) = fs::create_dir_all(dir) {
+            return Err(e.to_string());
+        }
+    }
+
+    // the file only gets its real name once it is complete and verified
+    let part_path = format!("{}.part", file.path);
+
+    // the mirror gets one try, the official url gets the retries
+    let mut url = super::network::rewrite_url(&file.url);
+    let mut attempt = 1;
+    while let Err(e) = download_to_part(&client, &url, &file, &part_path, &downloaded).await {
+        if url != file.url {
+            println!("{e}, trying the official url.");
+            url = file.url.clone();
+            continue;
+        }
+        if attempt >= MAX_DOWNLOAD_ATTEMPTS {
+            return Err(e);
+        }
+        println!("{e}, trying again.");
+        backoff(attempt).await;
+        attempt += 1;
+    }
+
+    if let Err(e) = fs::rename(&part_path, &file.path) {
+        return Err(e.to_string());
+    }
+
+    if is_natives_jar(&file.path) {
+        extract_natives(&file.path)?;
+    }
+
+    Ok(())
+}
+
+// Continues a .part file left by an interrupted download if the server supports ranges.
+async fn download_to_part(
+    client: &Client,
+    url: &str,
+    file: &Download,
+    part_path: &str,
+    progress: &AtomicU64,
+) -> Result<(), String> {
+    let downloaded = fs::metadata(part_path).map(|m| m.len()).unwrap_or(0);
+
+    let mut request = client.get(url);
+    if downloaded > 0 {
+        request = request.header(RANGE, format!("bytes={}-", downloaded));
+    }
+
+    let mut response = match request.send().await {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("Failed to download {}: {e}", file.path)),
+    };
+
+    let status = response.status();
+    if status == StatusCode::RANGE_NOT_SATISFIABLE {
+        // the .part is already complete or doesn't belong to this file
+        let _ = fs::remove_file(part_path);
+        return Err(format!("Failed to resume {}", file.path));
+    }
+    if !status.is_success() {
+        return Err(format!("Failed to download {}: {status}", file.path));
+    }
+
+    // servers without range support send the whole file again
+    let append = status == StatusCode::PARTIAL_CONTENT;
+    let mut part = match fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(part_path)
+    {
+        Ok(ok) => ok,
+        Err(e) => return Err(e.to_string()),
+    };
+    progress.store(if append { downloaded } else { 0 }, Ordering::Relaxed);
+
+    loop {
+        match response.chunk().await {
+            Ok(Some(chunk)) => {
+                if let Err(e) = part.write_all(&chunk) {
+                    return Err(e.to_string());
+                }
+                progress.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+            }
+            Ok(None) => break,
+            Err(e) => return Err(format!("Failed to download {}: {e}", file.path)),
+        }
+    }
+
+    let bytes = match fs::read(part_path) {
+        Ok(ok) => ok,
+        Err(e) => return Err(e.to_string()),
+    };
+    if let Err(e) = file.verify(&bytes) {
+        let _ = fs::remove_file(part_path);
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+fn extract_natives(path: &str) -> Result<(), String> {
+    let nativesfile = match File::open(path) {
+        Ok(ok) => ok,
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut archive = match ZipArchive::new(BufReader::new(nativesfile)) {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("Failed to open {path}: {e}")),
+    };
+
+    let folder_to_store_natives = match Path::new(path).parent() {
+        Some(some) => some.to_string_lossy().into_owned(),
+        None => return Err(format!("{path} has no parent folder")),
+    };
+
+    for i in 0..archive.len() {
+        let mut file = match archive.by_index(i) {
+            Ok(ok) => ok,
+            Err(e) => return Err(e.to_string()),
+        };
+        let outpath = format!(
+            "{}/{}",
+            &folder_to_store_natives,
+            file.mangled_name().to_string_lossy()
+        );
+        if file.is_dir() {
+            println!("Creating directory: {:?}", outpath);
+            if let Err(e) = fs::create_dir_all(&outpath) {
+                return Err(e.to_string());
+            }
+        } else {
+            println!("Extracting file: {:?}", outpath);
+            let result = File::create(&outpath)
+                .and_then(|mut outfile| std::io::copy(&mut file, &mut outfile));
+            if let Err(e) = result {
+                return Err(e.to_string());
+            }
+        }
+    }
+
+    match fs::remove_file(path) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
 }
 #[derive(Clone, Debug, PartialEq)]
 pub struct Download {
     pub path: String,
     pub url: String,
+    pub sha1: Option<String>,
+    pub size: Option<u64>,
+}
+
+impl Download {
+    // Checks that the bytes match the expected size and hash, if they are known.
+    pub fn verify(&self, bytes: &[u8]) -> Result<(), String> {
+        if let Some(size) = self.size {
+            if bytes.len() as u64 != size {
+                return Err(format!(
+                    "Size mismatch for {}: expected {} bytes, got {}",
+                    self.path,
+                    size,
+                    bytes.len()
+                ));
+            }
+        }
+        if let Some(sha1) = &self.sha1 {
+            if get_sha1(bytes) != sha1.to_lowercase() {
+                return Err(format!("Checksum mismatch for {}", self.path));
+            }
+        }
+        Ok(())
+    }
+
+    // A missing or corrupt file needs to be downloaded again. Hashing is slow, so it can be skipped.
+    pub fn is_valid(&self, check_hash: bool) -> bool {
+        let metadata = match fs::metadata(&self.path) {
+            Ok(ok) => ok,
+            Err(_) => return false,
+        };
+        if metadata.len() == 0 || self.size.is_some_and(|size| size != metadata.len()) {
+            return false;
+        }
+        if !check_hash || self.sha1.is_none() {
+            return true;
+        }
+
+        match fs::read(&self.path) {
+            Ok(bytes) => self.verify(&bytes).is_ok(),
+            Err(_) => false,
+        }
+    }
+}
+
+// Files checked by each verify step.
+const VERIFY_CHUNK_SIZE: usize = 100;
+
+// What verify and repair found, missing and corrupt files are downloaded again.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepairSummary {
+    pub version: String,
+    pub checked: usize,
+    pub missing: usize,
+    pub corrupt: usize,
+    // made by the forge installer, only reinstalling can fix them
+    pub unrepairable: Vec<String>,
+}
+
+impl std::fmt::Display for RepairSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.missing + self.corrupt {
+            0 => write!(
+                f,
+                "{}: checked {} files, no problems found.",
+                self.version, self.checked
+            )?,
+            _ => write!(
+                f,
+                "{}: checked {} files, downloaded {} missing and {} corrupt files again.",
+                self.version, self.checked, self.missing, self.corrupt
+            )?,
+        }
+        if !self.unrepairable.is_empty() {
+            write!(
+                f,
+                " {} files made by the mod loader installer are broken, reinstall the version.",
+                self.unrepairable.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Verification {
+    files: Vec<Download>,
+    total: usize,
+    to_download: Vec<Download>,
+    generated_files: Vec<String>,
+    summary: RepairSummary,
+    client: Client,
+    concurrency: usize,
+}
+
+impl Verification {
+    fn add_invalid_file(&mut self, file: Download) {
+        if self.generated_files.contains(&file.path) {
+            self.summary.unrepairable.push(file.path);
+            return;
+        }
+
+        match Path::new(&file.path).exists() {
+            true => self.summary.corrupt += 1,
+            false => self.summary.missing += 1,
+        }
+        self.to_download.push(file);
+    }
+}
+
+fn read_json(path: &str) -> Result<Value, String> {
+    match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn get_asset_index_download(mc_dir: &str, vanilla_json: &Value) -> Download {
+    Download {
+        path: format!(
+            "{}/assets/indexes/{}.json",
+            mc_dir,
+            vanilla_json["assets"].as_str().unwrap_or_default()
+        ),
+        url: vanilla_json["assetIndex"]["url"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        sha1: vanilla_json["assetIndex"]["sha1"]
+            .as_str()
+            .map(|s| s.to_owned()),
+        size: vanilla_json["assetIndex"]["size"].as_u64(),
+    }
+}
+
+// Downloads the asset index again if it is missing or corrupt and returns it.
+pub async fn get_asset_index(
+    client: &Client,
+    mc_dir: &str,
+    vanilla_json: &Value,
+) -> Result<Value, String> {
+    let asset_index = get_asset_index_download(mc_dir, vanilla_json);
+    if !asset_index.is_valid(true) {
+        download_file(client.clone(), asset_index.clone(), Arc::default()).await?;
+    }
+    read_json(&asset_index.path)
+}
+
+// Reads the version json, asset index and libraries again to list every file of the version.
+async fn get_repair_list(version: &str, concurrency: usize) -> Result<Verification, String> {
+    let mc_dir = super::launcher::get_minecraft_dir();
+    let version_folder = format!("{}/versions/{}", mc_dir, version);
+    let client = super::network::client();
+
+    let mut summary = RepairSummary {
+        version: version.to_owned(),
+        ..Default::default()
+    };
+    let mut count_invalid = |path: &str| match Path::new(path).exists() {
+        true => summary.corrupt += 1,
+        false => summary.missing += 1,
+    };
+
+    let version_json = match read_json(&format!("{}/{}.json", version_folder, version)) {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("Failed to read {version} json: {e}")),
+    };
+
+    // modded versions keep a copy of their vanilla json
+    let vanilla_json = match version_json["inheritsFrom"].as_str() {
+        Some(vanilla_version) => {
+            let json_path = format!("{}/{}.json", version_folder, vanilla_version);
+            match read_json(&json_path) {
+                Ok(ok) => ok,
+                Err(_) => {
+                    count_invalid(&json_path);
+                    match downloadversionjson(
+                        &VersionType::Vanilla,
+                        vanilla_version,
+                        &version_folder,
+                        &client,
+                    )
+                    .await
+                    {
+                        Ok(ok) => ok,
+                        Err(e) => {
+                            return Err(format!("Failed to download {vanilla_version} json: {e}"))
+                        }
+                    }
+                }
+            }
+        }
+        None => version_json.clone(),
+    };
+
+    // the assets come from the index, so it is repaired first
+    let asset_index = get_asset_index_download(&mc_dir, &vanilla_json);
+    if !asset_index.is_valid(true) {
+        count_invalid(&asset_index.path);
+        download_file(client.clone(), asset_index.clone(), Arc::default()).await?;
+    }
+    let asset_index_json = read_json(&asset_index.path)?;
+
+    let mut files = vec![Download {
+        path: format!("{}/{}.jar", version_folder, version),
+        url: vanilla_json["downloads"]["client"]["url"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        sha1: vanilla_json["downloads"]["client"]["sha1"]
+            .as_str()
+            .map(|s| s.to_owned()),
+        size: vanilla_json["downloads"]["client"]["size"].as_u64(),
+    }];
+
+    let mut libraries = vanilla_json["libraries"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    if !version_json["inheritsFrom"].is_null() {
+        libraries.extend(
+            version_json["libraries"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default(),
+        );
+    }
+    match get_libraries(&mc_dir, &libraries, &version_folder) {
+        Ok(ok) => files.extend(ok),
+        Err(e) => return Err(e.to_string()),
+    }
+
+    // the natives jars are removed once extracted, they are only needed if the natives are gone
+    let has_natives = fs::read_dir(format!("{}/natives", version_folder))
+        .is_ok_and(|mut entries| entries.next().is_some());
+    if has_natives {
+        files.retain(|file| !is_natives_jar(&file.path));
+    }
+
+    files.extend(get_assets(&mc_dir, asset_index_json)?);
+
+    // the vanilla and loader jsons can list the same library, assets can share a hash,
+    // each natives jar has its own path so none of them is dropped
+    let mut paths = HashSet::new();
+    files.retain(|file| paths.insert(file.path.clone()));
+
+    // forge installer outputs have no url
+    let generated_files = libraries
+        .iter()
+        .filter(|library| library["downloads"]["artifact"]["url"].as_str() == Some(""))
+        .filter_map(|library| library["name"].as_str())
+        .map(|name| {
+            format!(
+                "{}/libraries/{}",
+                mc_dir,
+                super::launcher::get_library_path(name)
+            )
+        })
+        .collect();
+
+    Ok(Verification {
+        total: files.len(),
+        files,
+        to_download: vec![],
+        generated_files,
+        summary,
+        client,
+        concurrency,
+    })
+}
+
+// Files under libraries and assets that no installed version uses.
+#[derive(Debug, Clone, Default)]
+pub struct UnusedFiles {
+    pub files: Vec<PathBuf>,
+    pub size: u64,
+    // forge versions without their installer, their libraries can't be told apart so all are kept
+    pub kept_libraries_for: Vec<String>,
+    folders: Vec<PathBuf>,
+}
+
+impl UnusedFiles {
+    // Deletes the files and the folders they leave empty, returns the freed bytes.
+    pub fn delete(&self) -> Result<u64, String> {
+        let mut freed = 0;
+        for file in &self.files {
+            let size = fs::metadata(file).map(|m| m.len()).unwrap_or(0);
+            if let Err(e) = fs::remove_file(file) {
+                return Err(format!("Failed to delete {}: {e}", file.display()));
+            }
+            freed += size;
+
+            let mut folder = file.parent();
+            while let Some(path) = folder {
+                if self.folders.iter().any(|root| root == path) || fs::remove_dir(path).is_err() {
+                    break;
+                }
+                folder = path.parent();
+            }
+        }
+
+        Ok(freed)
+    }
+}
+
+// Reads every installed version to find the libraries, asset indexes and objects nothing uses.
+pub fn get_unused_files() -> Result<UnusedFiles, String> {
+    let mc_dir = super::launcher::get_minecraft_dir();
+    let mut used_files: HashSet<PathBuf> = HashSet::new();
+    let mut kept_libraries_for = vec![];
+
+    let versions = match fs::read_dir(format!("{}/versions", mc_dir)) {
+        Ok(ok) => ok,
+        Err(e) => return Err(e.to_string()),
+    };
+    for entry in versions.flatten() {
+        if !entry.path().is_dir() {
+            continue;
+        }
+        let version = entry.file_name().to_string_lossy().to_string();
+        let version_folder = format!("{}/versions/{}", mc_dir, version);
+
+        // a file of a broken version could be deleted by mistake
+        let version_json = match read_json(&format!("{}/{}.json", version_folder, version)) {
+            Ok(ok) => ok,
+            Err(e) => {
+                return Err(format!(
+                    "Failed to read {version} json, repair or uninstall it first: {e}"
+                ))
+            }
+        };
+        let vanilla_json = match version_json["inheritsFrom"].as_str() {
+            Some(vanilla_version) => {
+                match read_json(&format!("{}/{}.json", version_folder, vanilla_version)).or_else(
+                    |_| {
+                        read_json(&format!(
+                            "{}/versions/{}/{}.json",
+                            mc_dir, vanilla_version, vanilla_version
+                        ))
+                    },
+                ) {
+                    Ok(ok) => ok,
+                    Err(e) => {
+                        return Err(format!(
+                            "Failed to read {vanilla_version} json of {version}: {e}"
+                        ))
+                    }
+                }
+            }
+            None => version_json.clone(),
+        };
+
+        let mut libraries = vanilla_json["libraries"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        if !version_json["inheritsFrom"].is_null() {
+            libraries.extend(
+                version_json["libraries"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default(),
+            );
+        }
+        used_files.extend(
+            get_library_downloads(&mc_dir, &libraries, &version_folder)
+                .into_iter()
+                .map(|file| PathBuf::from(file.path)),
+        );
+
+        match super::launcher::get_loader(&version_json) {
+            super::launcher::Loader::Forge | super::launcher::Loader::NeoForge => {
+                match super::forge::get_installer_libraries(&version) {
+                    Ok(paths) => used_files.extend(
+                        paths
+                            .iter()
+                            .map(|path| PathBuf::from(format!("{}/libraries/{}", mc_dir, path))),
+                    ),
+                    Err(_) => kept_libraries_for.push(version.clone()),
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(assets) = vanilla_json["assets"].as_str() {
+            let asset_index_path = format!("{}/assets/indexes/{}.json", mc_dir, assets);
+            let asset_index_json = match read_json(&asset_index_path) {
+                Ok(ok) => ok,
+                Err(e) => {
+                    return Err(format!(
+                        "Failed to read the asset index of {version}, repair it first: {e}"
+                    ))
+                }
+            };
+            used_files.insert(PathBuf::from(asset_index_path));
+            used_files.extend(
+                get_asset_downloads(&mc_dir, &asset_index_json)
+                    .into_iter()
+                    .map(|file| PathBuf::from(file.path)),
+            );
+        }
+    }
+
+    let mut folders = vec![
+        PathBuf::from(format!("{}/assets/objects", mc_dir)),
+        PathBuf::from(format!("{}/assets/indexes", mc_dir)),
+    ];
+    if kept_libraries_for.is_empty() {
+        folders.push(PathBuf::from(format!("{}/libraries", mc_dir)));
+    }
+
+    let mut unused_files = UnusedFiles {
+        kept_libraries_for,
+        folders: folders.clone(),
+        ..Default::default()
+    };
+    while let Some(folder) = folders.pop() {
+        let Ok(entries) = fs::read_dir(&folder) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // .part files belong to downloads that can be resumed
+            if path.extension().is_some_and(|ext| ext == "part") {
+                continue;
+            }
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => folders.push(path),
+                Ok(_) if !used_files.contains(&path) => {
+                    unused_files.size += entry.metadata().map(|m| m.len()).unwrap_or(0);
+                    unused_files.files.push(path);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(unused_files)
+}
+
+fn get_java_part_path(folder: &str, java: u32) -> String {
+    match std::env::consts::OS {
+        "windows" => format!("{}/java{}.zip.part", folder, java),
+        _ => format!("{}/java{}.tar.gz.part", folder, java),
+    }
+}
+
+// Goes back to PreparingJavaDownload, which waits before trying again.
+fn retry_java_download<I>(id: I, java: u32, attempt: u32, error: String) -> ((I, Progress), State) {
+    if attempt >= MAX_DOWNLOAD_ATTEMPTS {
+        return ((id, Progress::Errored(error)), State::Idle);
+    }
+    println!("{error}, trying again.");
+
+    (
+        (
+            id,
+            Progress::JavaDownloadRetrying(format!(
+                "{error}, trying again ({attempt}/{})",
+                MAX_DOWNLOAD_ATTEMPTS - 1
+            )),
+        ),
+        State::PreparingJavaDownload(java, attempt + 1),
+    )
+}
+
+// A Java runtime from Mojang's java-runtime manifest, downloaded file by file.
+pub struct JavaRuntime {
+    folder: String,
+    // the files go here and replace the folder once they are all downloaded
+    download_folder: String,
+    version: String,
+    files: Vec<JavaRuntimeFile>,
+    links: Vec<(String, String)>,
+    downloaded: u64,
+    total: u64,
+    client: Client,
+}
+
+struct JavaRuntimeFile {
+    path: String,
+    url: String,
+    sha1: String,
+    size: u64,
+    executable: bool,
 }
 
 async fn download<I: 'static + Hash + Copy + Send + Sync>(
@@ -119,7 +1077,7 @@ async fn download<I: 'static + Hash + Copy + Send + Sync>(
 ) -> ((I, Progress), State) {
     match state {
         // Versions downloading
-        State::GettingDownloadList(version, version_type) => {
+        State::GettingDownloadList(version, version_type, settings) => {
             let mc_dir = match std::env::consts::OS {
                 "linux" => format!("{}/.minecraft", std::env::var("HOME").unwrap()),
                 "windows" => format!(
@@ -129,18 +1087,15 @@ async fn download<I: 'static + Hash + Copy + Send + Sync>(
                 _ => panic!("System not supported."),
             };
 
-            let version_name = match version_type {
-                VersionType::Vanilla => version.clone(),
-                VersionType::Fabric => format!("{}-fabric", &version),
-            };
+            let version_name = version_type.get_version_name(&version);
 
             let version_folder = format!("{}/versions/{}", &mc_dir, version_name);
 
-            let client = Client::new();
+            let client = super::network::client();
 
             // the fabric json doesn't provide all required files url, so we are going to get the vanilla json for fabric.
             let vanilla_version_json = match version_type {
-                VersionType::Vanilla => {
+                VersionType::Vanilla | VersionType::Forge(_) | VersionType::NeoForge(_) => {
                     match downloadversionjson(&version_type, &version, &version_folder, &client)
                         .await
                     {
@@ -148,7 +1103,7 @@ async fn download<I: 'static + Hash + Copy + Send + Sync>(
                         Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
                     }
                 }
-                VersionType::Fabric => {
+                VersionType::Fabric(_) | VersionType::Quilt(_) => {
                     match downloadversionjson(&version_type, &version, &version_folder, &client)
                         .await
                     {
@@ -164,38 +1119,35 @@ async fn download<I: 'static + Hash + Copy + Send + Sync>(
                 }
             };
 
-            let version_json = super::getjson(format!("{}/{}.json", version_folder, version_name));
-
-            // asset index, we need this file to get assets
-            let asset_index_download = match client
-                .get(vanilla_version_json["assetIndex"]["url"].as_str().unwrap())
-                .send()
+            // forge and neoforge jsons come from their installer
+            let mut forge_install = None;
+            let mut forge_libraries = vec![];
+            if let Some(installer_url) = version_type.get_installer_url(&version) {
+                match super::forge::prepare(
+                    &client,
+                    installer_url,
+                    &version,
+                    &version_name,
+                    settings.java.clone(),
+                )
                 .await
-            {
-                Ok(ok) => ok.bytes().await.unwrap(),
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
-            };
-
-            let asset_index_path = format!(
-                "{}/assets/indexes/{}.json",
-                mc_dir,
-                vanilla_version_json["assets"].as_str().unwrap()
-            );
-            match fs::create_dir_all(format!("{}/assets/indexes", mc_dir)) {
-                Ok(ok) => ok,
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
+                {
+                    Ok((install, libraries)) => {
+                        forge_install = Some(install);
+                        forge_libraries = libraries;
+                    }
+                    Err(e) => return ((id, Progress::Errored(e)), State::Idle),
+                }
             }
-            let mut asset_index_file = match File::create(&asset_index_path) {
-                Ok(ok) => ok,
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
-            };
 
-            match asset_index_file.write_all(&asset_index_download) {
-                Ok(ok) => ok,
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
-            }
+            let version_json = super::getjson(format!("{}/{}.json", version_folder, version_name));
 
-            let asset_index_json = super::getjson(asset_index_path);
+            // asset index, we need this file to get assets
+            let asset_index_json =
+                match get_asset_index(&client, &mc_dir, &vanilla_version_json).await {
+                    Ok(ok) => ok,
+                    Err(e) => return ((id, Progress::Errored(e)), State::Idle),
+                };
 
             // variable to store download list
             let mut download_list = vec![];
@@ -207,6 +1159,10 @@ async fn download<I: 'static + Hash + Copy + Send + Sync>(
                     .as_str()
                     .unwrap()
                     .to_string(),
+                sha1: vanilla_version_json["downloads"]["client"]["sha1"]
+                    .as_str()
+                    .map(|s| s.to_owned()),
+                size: vanilla_version_json["downloads"]["client"]["size"].as_u64(),
             });
 
             // push assets
@@ -228,8 +1184,10 @@ async fn download<I: 'static + Hash + Copy + Send + Sync>(
             };
 
             download_list.extend_from_slice(libraries);
-            if version_type == VersionType::Fabric {
-                // fabric libraries
+            if forge_install.is_some() {
+                download_list.extend(forge_libraries);
+            } else if version_type != VersionType::Vanilla {
+                // loader libraries
                 let libresult = &get_libraries(
                     &mc_dir,
                     version_json["libraries"].as_array().unwrap(),
@@ -242,9 +1200,10 @@ async fn download<I: 'static + Hash + Copy + Send + Sync>(
                 download_list.extend_from_slice(libraries);
             }
 
+            // corrupt files are downloaded again
             let mut filtered_download_list = Vec::new();
             for i in download_list {
-                if !Path::new(&i.path).exists() {
+                if !i.is_valid(true) {
                     filtered_download_list.push(i)
                 }
             }
@@ -254,128 +1213,59 @@ async fn download<I: 'static + Hash + Copy + Send + Sync>(
                     id,
                     Progress::GotDownloadList(filtered_download_list.len() as i32),
                 ),
-                State::Downloading(DownloadList {
-                    download_list: filtered_download_list,
-                    client,
-                }),
+                State::Downloading(
+                    ParallelDownload::new(DownloadList {
+                        download_list: filtered_download_list,
+                        client,
+                        concurrency: settings.concurrency,
+                    }),
+                    forge_install,
+                ),
             )
         }
 
-        State::Downloading(download_list) => {
-            if download_list.download_list.is_empty() {
-                println!("finished");
-                return ((id, Progress::Finished), State::Idle);
-            }
-
-            let mut list = download_list.download_list.clone();
-
-            let current_file_to_download = list.remove(0);
-            println!("Downloading {}", current_file_to_download.path);
-
-            let current_download = match download_list
-                .client
-                .get(current_file_to_download.url)
-                .send()
-                .await
-            {
-                Ok(ok) => ok.bytes().await.unwrap(),
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
-            };
-
-            let path = Path::new(&current_file_to_download.path);
-
-            let mut path_vec  = vec![];
-            for i in path.components(){
-                path_vec.push(i.as_os_str().to_string_lossy())
-            }
-
-            if path_vec.len() > 1{
-                path_vec.pop();
-                let dir = path_vec.join("/");
-                
-                if !Path::new(&dir).exists(){
-                    match fs::create_dir_all(dir){
-                        Ok(ok) => ok,
-                        Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
-                    }
+        State::Downloading(mut download, forge_install) => match download.next().await {
+            Some(Ok(progress)) => (
+                (id, Progress::Downloaded(progress)),
+                State::Downloading(download, forge_install),
+            ),
+            Some(Err(e)) => ((id, Progress::Errored(e)), State::Idle),
+            None => match forge_install {
+                Some(install) => (
+                    (id, Progress::RunningForgeProcessors),
+                    State::RunningForgeProcessors(install),
+                ),
+                None => {
+                    println!("finished");
+                    ((id, Progress::Finished), State::Idle)
                 }
-
-
-            }
-
-            let mut file = match File::create(&current_file_to_download.path) {
-                Ok(file) => file,
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
-            };
-
-            match file.write_all(&current_download) {
-                Ok(ok) => ok,
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
-            }
-
-            if current_file_to_download.path.contains("natives.jar") {
-                let nativesfile = File::open(&current_file_to_download.path).unwrap();
-                let reader = BufReader::new(nativesfile);
-                let mut archive = ZipArchive::new(reader).unwrap();
-
-                let folder_to_store_natives =
-                    &current_file_to_download.path.replace("/natives.jar", "");
-
-                for i in 0..archive.len() {
-                    let mut file = archive.by_index(i).unwrap();
-                    let outpath = format!(
-                        "{}/{}",
-                        &folder_to_store_natives,
-                        file.mangled_name().to_string_lossy()
-                    );
-                    if file.is_dir() {
-                        println!("Creating directory: {:?}", outpath);
-                        std::fs::create_dir_all(&outpath).unwrap();
-                    } else {
-                        println!("Extracting file: {:?}", outpath);
-                        let mut outfile = File::create(&outpath).unwrap();
-                        std::io::copy(&mut file, &mut outfile).unwrap();
-                    }
+            },
+        },
+        State::RunningForgeProcessors(install) => {
+            // processors can take minutes, keep them off the async threads
+            let result =
+                tokio::task::spawn_blocking(move || super::forge::run_processors(&install)).await;
+
+            match result {
+                Ok(Ok(_)) => {
+                    println!("finished");
+                    ((id, Progress::Finished), State::Idle)
                 }
-                fs::remove_file(&current_file_to_download.path).unwrap();
-            };
-
-            println!("starting next download.");
-
-            (
-                (id, Progress::Downloaded(list.len() as i32)),
-                State::Downloading(DownloadList {
-                    download_list: list,
-                    client: download_list.client,
-                }),
-            )
+                Ok(Err(e)) => ((id, Progress::Errored(e)), State::Idle),
+                Err(e) => ((id, Progress::Errored(e.to_string())), State::Idle),
+            }
         }
         // Idle
         State::Idle => iced::futures::future::pending().await,
-        State::PreparingJavaDownload(java) => {
-            let os = std::env::consts::OS;
-            let java_url = match java{
-                Java::J8 => {
-                    match os{
-                        "windows" => "https://github.com/adoptium/temurin8-binaries/releases/download/jdk8u412-b08/OpenJDK8U-jre_x64_windows_hotspot_8u412b08.zip",
-                        "linux" => "https://github.com/adoptium/temurin8-binaries/releases/download/jdk8u412-b08/OpenJDK8U-jre_x64_linux_hotspot_8u412b08.tar.gz",
-                        _ => panic!("Unsuported system.")
-                    }
-                },
-                Java::J17 => {
-                    match os{
-                        "windows" => "https://github.com/adoptium/temurin17-binaries/releases/download/jdk-17.0.9%2B9.1/OpenJDK17U-jre_x64_windows_hotspot_17.0.9_9.zip",
-                        "linux" => "https://github.com/adoptium/temurin17-binaries/releases/download/jdk-17.0.11%2B9/OpenJDK17U-jre_x64_linux_hotspot_17.0.11_9.tar.gz",
-                        _ => panic!("Unsuported system.")
-                    }
-                },
-                Java::J21 => {
-                    match os{
-                        "windows" => "https://github.com/adoptium/temurin21-binaries/releases/download/jdk-21.0.3%2B9/OpenJDK21U-jre_x64_windows_hotspot_21.0.3_9.zip",
-                        "linux" => "https://github.com/adoptium/temurin21-binaries/releases/download/jdk-21.0.3%2B9/OpenJDK21U-jre_x64_linux_hotspot_21.0.3_9.tar.gz",
-                        _ => panic!("Unsuported system.")
-                    }
-                },
+        State::PreparingJavaDownload(java, attempt) => {
+            if attempt > 1 {
+                backoff(attempt - 1).await;
+            }
+
+            let client = super::network::client();
+            let package = match get_adoptium_package(&client, java).await {
+                Ok(ok) => ok,
+                Err(e) => return retry_java_download(id, java, attempt, e),
             };
 
             let mc_dir = match std::env::consts::OS {
@@ -394,38 +1284,81 @@ async fn download<I: 'static + Hash + Copy + Send + Sync>(
                 Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
             }
 
-            let download = reqwest::get(java_url).await;
-
-            let file_name = match os {
-                "linux" => "compressed.tar.gz",
-                "windows" => "compressed.zip",
-                _ => panic!("System not supported."),
-            };
-
-            let file_to_write =
-                match File::create(format!("{}/{}", folder_to_store_download, file_name)) {
+            // an interrupted download continues from its .part file
+            let part_path = get_java_part_path(&folder_to_store_download, java);
+            let mut hasher = Sha256::new();
+            let downloaded = match File::open(&part_path) {
+                Ok(mut part) => match std::io::copy(&mut part, &mut hasher) {
                     Ok(ok) => ok,
                     Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
-                };
+                },
+                Err(_) => 0,
+            };
 
-            match download {
-                Ok(d) => {
-                    let size = d.content_length().unwrap();
-                    (
-                        (id, Progress::StartedJavaDownload((size / 1048576) as u8)),
-                        State::DownloadingJava {
-                            downloaded: 0,
-                            total: size,
-                            download: d,
-                            folder_to_store: folder_to_store_download,
-                            file_to_write,
-                            java,
-                        },
-                    )
-                }
+            let mut request = client.get(&package.url);
+            if downloaded > 0 {
+                request = request.header(RANGE, format!("bytes={}-", downloaded));
+            }
 
-                Err(e) => ((id, Progress::Errored(e.to_string())), State::Idle),
+            let download = match request.send().await {
+                Ok(ok) => ok,
+                Err(e) => return retry_java_download(id, java, attempt, e.to_string()),
+            };
+
+            let status = download.status();
+            if status == StatusCode::RANGE_NOT_SATISFIABLE {
+                let _ = fs::remove_file(&part_path);
+                return retry_java_download(
+                    id,
+                    java,
+                    attempt,
+                    format!("Failed to resume Java {java}"),
+                );
+            }
+            if !status.is_success() {
+                return retry_java_download(
+                    id,
+                    java,
+                    attempt,
+                    format!("Failed to download Java {java}: {status}"),
+                );
             }
+
+            let resumed = status == StatusCode::PARTIAL_CONTENT;
+            let (downloaded, hasher) = match resumed {
+                true => (downloaded, hasher),
+                false => (0, Sha256::new()),
+            };
+
+            let file_to_write = match fs::OpenOptions::new()
+                .create(true)
+                .write(true)
+                .append(resumed)
+                .truncate(!resumed)
+                .open(&part_path)
+            {
+                Ok(ok) => ok,
+                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
+            };
+
+            let size = downloaded
+                + download
+                    .content_length()
+                    .unwrap_or(package.size.saturating_sub(downloaded));
+            (
+                (id, Progress::StartedJavaDownload((size / 1048576) as u16)),
+                State::DownloadingJava {
+                    downloaded,
+                    total: size,
+                    download,
+                    folder_to_store: folder_to_store_download,
+                    file_to_write,
+                    java,
+                    checksum: package.checksum,
+                    hasher,
+                    attempt,
+                },
+            )
         }
         State::DownloadingJava {
             downloaded,
@@ -434,16 +1367,20 @@ async fn download<I: 'static + Hash + Copy + Send + Sync>(
             folder_to_store,
             mut file_to_write,
             java,
+            checksum,
+            mut hasher,
+            attempt,
         } => match download.chunk().await {
             Ok(Some(chunk)) => {
                 let downloaded = downloaded + chunk.len() as u64;
                 let percentage = ((downloaded as f32 / total as f32) * 100.0) as u8;
-                let mb_downloaded = (downloaded / 1048576) as u8;
+                let mb_downloaded = (downloaded / 1048576) as u16;
 
                 match file_to_write.write_all(&chunk) {
                     Ok(ok) => ok,
                     Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
                 }
+                hasher.update(&chunk);
                 (
                     (
                         id,
@@ -456,14 +1393,42 @@ async fn download<I: 'static + Hash + Copy + Send + Sync>(
                         folder_to_store,
                         file_to_write,
                         java,
+                        checksum,
+                        hasher,
+                        attempt,
                     },
                 )
             }
-            Ok(None) => (
-                (id, Progress::JavaDownloadFinished),
-                State::ExtractingJava(folder_to_store, java),
-            ),
-            Err(e) => ((id, Progress::Errored(e.to_string())), State::Idle),
+            Ok(None) => {
+                let part_path = get_java_part_path(&folder_to_store, java);
+
+                // the archive is only extracted if it matches the checksum published by Adoptium
+                if format!("{:x}", hasher.finalize()) != checksum {
+                    let _ = fs::remove_file(&part_path);
+                    return retry_java_download(
+                        id,
+                        java,
+                        attempt,
+                        format!("Java {java} download is corrupted, checksum doesn't match."),
+                    );
+                }
+
+                let file_name = match std::env::consts::OS {
+                    "windows" => "compressed.zip",
+                    _ => "compressed.tar.gz",
+                };
+                if let Err(e) = fs::rename(&part_path, format!("{}/{}", folder_to_store, file_name))
+                {
+                    return ((id, Progress::Errored(e.to_string())), State::Idle);
+                }
+
+                (
+                    (id, Progress::JavaDownloadFinished),
+                    State::ExtractingJava(folder_to_store, java),
+                )
+            }
+            // the .part is kept, the next attempt continues from it
+            Err(e) => retry_java_download(id, java, attempt, e.to_string()),
         },
 
         State::ExtractingJava(folder, java) => {
@@ -480,11 +1445,7 @@ async fn download<I: 'static + Hash + Copy + Send + Sync>(
                 Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
             };
 
-            let java_folder_name = match java {
-                Java::J8 => "java8",
-                Java::J17 => "java17",
-                Java::J21 => "java21",
-            };
+            let java_folder_name = format!("java{}", java);
 
             let mut f_folder_name = String::new();
 
@@ -540,86 +1501,164 @@ async fn download<I: 'static + Hash + Copy + Send + Sync>(
                 _ => panic!("System not supported."),
             }
 
-            fs::rename(
-                format!("{}/{}", folder, f_folder_name),
-                format!("{}/{}", folder, java_folder_name),
-            )
-            .unwrap();
+            // reinstalling replaces the old files
+            if let Err(e) = replace_folder(
+                &format!("{}/{}", folder, f_folder_name),
+                &format!("{}/{}", folder, java_folder_name),
+            ) {
+                return ((id, Progress::Errored(e)), State::Idle);
+            }
             fs::remove_file(format!("{}/{}", folder, file_name)).unwrap();
 
             ((id, Progress::JavaExtracted), State::Idle)
         }
-        State::DownloadingMissingFiles(download_list) => {
-            if download_list.download_list.is_empty() {
-                println!("finished");
-                return ((id, Progress::MissingFilesDownloadFinished), State::Idle);
+        State::PreparingJavaRuntime(component) => {
+            match get_java_runtime(super::network::client(), &component).await {
+                Ok(runtime) => (
+                    (
+                        id,
+                        Progress::StartedJavaDownload((runtime.total / 1048576) as u16),
+                    ),
+                    State::DownloadingJavaRuntime(runtime),
+                ),
+                Err(e) => ((id, Progress::Errored(e)), State::Idle),
             }
+        }
+        State::DownloadingJavaRuntime(mut runtime) => {
+            let file = match runtime.files.pop() {
+                Some(file) => file,
+                None => {
+                    #[cfg(unix)]
+                    for (path, target) in &runtime.links {
+                        if let Err(e) = std::os::unix::fs::symlink(target, path) {
+                            println!("Failed to create link {path}, ignoring. -> {e}")
+                        }
+                    }
 
-            let mut list = download_list.download_list.clone();
-
-            let current_file_to_download = list.remove(0);
-            println!("Downloading {}", current_file_to_download.path);
+                    // written last, marks the runtime as installed
+                    let version_file = format!("{}/.version", runtime.download_folder);
+                    if let Err(e) = fs::write(version_file, &runtime.version) {
+                        return ((id, Progress::Errored(e.to_string())), State::Idle);
+                    }
+                    if let Err(e) = replace_folder(&runtime.download_folder, &runtime.folder) {
+                        return ((id, Progress::Errored(e)), State::Idle);
+                    }
 
-            let current_download = match download_list
-                .client
-                .get(current_file_to_download.url)
-                .send()
-                .await
-            {
-                Ok(ok) => ok.bytes().await.unwrap(),
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
+                    return ((id, Progress::JavaExtracted), State::Idle);
+                }
             };
 
-            let mut file = match File::create(&current_file_to_download.path) {
-                Ok(file) => file,
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
+            let download = Download {
+                path: file.path.clone(),
+                url: file.url.clone(),
+                sha1: Some(file.sha1.clone()),
+                size: Some(file.size),
             };
+            if let Err(e) = download_file(runtime.client.clone(), download, Arc::default()).await {
+                return ((id, Progress::Errored(e)), State::Idle);
+            }
 
-            match file.write_all(&current_download) {
-                Ok(ok) => ok,
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
+            #[cfg(unix)]
+            if file.executable {
+                use std::os::unix::fs::PermissionsExt;
+                if let Err(e) = fs::set_permissions(&file.path, fs::Permissions::from_mode(0o755)) {
+                    return ((id, Progress::Errored(e.to_string())), State::Idle);
+                }
+            }
+
+            runtime.downloaded += file.size;
+            let percentage = ((runtime.downloaded as f32 / runtime.total as f32) * 100.0) as u8;
+
+            (
+                (
+                    id,
+                    Progress::JavaDownloadProgressed(
+                        (runtime.downloaded / 1048576) as u16,
+                        percentage,
+                    ),
+                ),
+                State::DownloadingJavaRuntime(runtime),
+            )
+        }
+        State::DownloadingMissingFiles(mut download) => match download.next().await {
+            Some(Ok(progress)) => (
+                (id, Progress::MissingFilesDownloadProgressed(progress)),
+                State::DownloadingMissingFiles(download),
+            ),
+            Some(Err(e)) => ((id, Progress::Errored(e)), State::Idle),
+            None => {
+                println!("finished");
+                ((id, Progress::MissingFilesDownloadFinished), State::Idle)
             }
+        },
 
-            if current_file_to_download.path.contains("natives.jar") {
-                let nativesfile = File::open(&current_file_to_download.path).unwrap();
-                let reader = BufReader::new(nativesfile);
-                let mut archive = ZipArchive::new(reader).unwrap();
-
-                let folder_to_store_natives =
-                    &current_file_to_download.path.replace("/natives.jar", "");
-
-                for i in 0..archive.len() {
-                    let mut file = archive.by_index(i).unwrap();
-                    let outpath = format!(
-                        "{}/{}",
-                        &folder_to_store_natives,
-                        file.mangled_name().to_string_lossy()
+        // Versions repair
+        State::PreparingRepair(version, concurrency) => {
+            match get_repair_list(&version, concurrency).await {
+                Ok(verification) => (
+                    (id, Progress::RepairVerifying(0, verification.total)),
+                    State::VerifyingFiles(verification),
+                ),
+                Err(e) => ((id, Progress::RepairErrored(e)), State::Idle),
+            }
+        }
+        State::VerifyingFiles(mut verification) => {
+            if verification.files.is_empty() {
+                if verification.to_download.is_empty() {
+                    return (
+                        (id, Progress::RepairFinished(verification.summary)),
+                        State::Idle,
                     );
-                    if file.is_dir() {
-                        println!("Creating directory: {:?}", outpath);
-                        std::fs::create_dir_all(&outpath).unwrap();
-                    } else {
-                        println!("Extracting file: {:?}", outpath);
-                        let mut outfile = File::create(&outpath).unwrap();
-                        std::io::copy(&mut file, &mut outfile).unwrap();
-                    }
                 }
-                fs::remove_file(&current_file_to_download.path).unwrap();
-            };
 
-            println!("starting next download.");
+                let download = ParallelDownload::new(DownloadList {
+                    download_list: verification.to_download,
+                    client: verification.client,
+                    concurrency: verification.concurrency,
+                });
+                return (
+                    (
+                        id,
+                        Progress::RepairDownloadProgressed(download.get_progress()),
+                    ),
+                    State::RepairingFiles(download, verification.summary),
+                );
+            }
+
+            // hashing is slow, the files are checked in chunks to show progress
+            let chunk_start = verification.files.len().saturating_sub(VERIFY_CHUNK_SIZE);
+            let chunk = verification.files.split_off(chunk_start);
+            let result = tokio::task::spawn_blocking(move || {
+                chunk
+                    .into_iter()
+                    .filter(|file| !file.is_valid(true))
+                    .collect::<Vec<_>>()
+            })
+            .await;
+
+            let invalid_files = match result {
+                Ok(ok) => ok,
+                Err(e) => return ((id, Progress::RepairErrored(e.to_string())), State::Idle),
+            };
+            for file in invalid_files {
+                verification.add_invalid_file(file);
+            }
 
+            let checked = verification.total - verification.files.len();
+            verification.summary.checked = checked;
             (
-                (
-                    id,
-                    Progress::MissingFilesDownloadProgressed(list.len() as u16),
-                ),
-                State::DownloadingMissingFiles(DownloadList {
-                    download_list: list,
-                    client: download_list.client,
-                }),
+                (id, Progress::RepairVerifying(checked, verification.total)),
+                State::VerifyingFiles(verification),
             )
         }
+        State::RepairingFiles(mut download, summary) => match download.next().await {
+            Some(Ok(progress)) => (
+                (id, Progress::RepairDownloadProgressed(progress)),
+                State::RepairingFiles(download, summary),
+            ),
+            Some(Err(e)) => ((id, Progress::RepairErrored(e)), State::Idle),
+            None => ((id, Progress::RepairFinished(summary)), State::Idle),
+        },
 
         State::PreparingUpdate(url) => {
             let exec_path = env::current_exe().unwrap();
@@ -633,7 +1672,7 @@ async fn download<I: 'static + Hash + Copy + Send + Sync>(
                 fs::set_permissions(&exec_path.with_extension("new"), permission).unwrap();
             }
 
-            let download = reqwest::get(url).await;
+            let download = super::network::client().get(url).send().await;
 
             match download {
                 Ok(d) => {
@@ -691,126 +1730,360 @@ async fn download<I: 'static + Hash + Copy + Send + Sync>(
     }
 }
 
-// Json file
-pub async fn downloadversionjson(
-    version_type: &VersionType,
-    version: &String,
-    foldertosave: &String,
+// Java runtime
+// Manifest url and version name of a Mojang runtime for this system.
+async fn get_java_runtime_entry(
     client: &Client,
-) -> Result<Value, reqwest::Error> {
-    match version_type {
-        VersionType::Vanilla => {
-            let versionlistjson = reqwest::Client::new()
-                .get("https://launchermeta.mojang.com/mc/game/version_manifest_v2.json")
-                .send()
-                .await?
-                .text()
-                .await?;
+    component: &str,
+) -> Result<(String, String), String> {
+    let platform = match super::java::get_runtime_platform() {
+        Some(some) => some,
+        None => return Err("Mojang Java runtimes are not available for this system.".to_owned()),
+    };
 
-            let content = serde_json::from_str(&versionlistjson);
+    let runtime_list = match super::network::send(
+        "https://launchermeta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json",
+        |url| client.get(url),
+    )
+    .await
+    {
+        Ok(ok) => ok.text().await.unwrap_or_default(),
+        Err(e) => return Err(format!("Failed to get Java runtime list: {e}")),
+    };
+    let runtime_list: Value = match serde_json::from_str(&runtime_list) {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("Failed to read Java runtime list: {e}")),
+    };
 
-            let p: Value = content.unwrap();
+    let runtime = &runtime_list[platform][component][0];
+    match (
+        runtime["manifest"]["url"].as_str(),
+        runtime["version"]["name"].as_str(),
+    ) {
+        (Some(url), Some(version)) => Ok((url.to_owned(), version.to_owned())),
+        _ => Err(format!("{component} is not available for {platform}.")),
+    }
+}
+
+// Moves the new folder in place of the old one, the old one comes back if it fails.
+fn replace_folder(new_folder: &str, folder: &str) -> Result<(), String> {
+    let old_folder = format!("{}.old", new_folder);
+    if Path::new(&old_folder).exists() {
+        if let Err(e) = fs::remove_dir_all(&old_folder) {
+            return Err(format!("Failed to remove {old_folder}: {e}"));
+        }
+    }
+
+    let has_old_folder = Path::new(folder).exists();
+    if has_old_folder {
+        if let Err(e) = fs::rename(folder, &old_folder) {
+            return Err(format!("Failed to move {folder}: {e}"));
+        }
+    }
+    if let Some(parent) = Path::new(folder).parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Err(e) = fs::rename(new_folder, folder) {
+        if has_old_folder {
+            let _ = fs::rename(&old_folder, folder);
+        }
+        return Err(format!("Failed to move {new_folder} to {folder}: {e}"));
+    }
+
+    if has_old_folder {
+        if let Err(e) = fs::remove_dir_all(&old_folder) {
+            println!("Failed to remove {old_folder}, ignoring. -> {e}");
+        }
+    }
+    Ok(())
+}
+
+async fn get_java_runtime(client: Client, component: &str) -> Result<JavaRuntime, String> {
+    let (manifest_url, version) = get_java_runtime_entry(&client, component).await?;
+
+    let manifest = match super::network::send(&manifest_url, |url| client.get(url)).await {
+        Ok(ok) => ok.text().await.unwrap_or_default(),
+        Err(e) => return Err(format!("Failed to get {component} manifest: {e}")),
+    };
+    let manifest: Value = match serde_json::from_str(&manifest) {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("Failed to read {component} manifest: {e}")),
+    };
+
+    let folder = super::java::get_runtime_dir(component);
+    // outside minelander_java, so it isn't listed as a runtime
+    let download_folder = format!(
+        "{}/minelander_java_download/{}",
+        super::launcher::get_minecraft_dir(),
+        component
+    );
+
+    // leftovers of an interrupted install, the installed runtime is kept until the new one is complete
+    if Path::new(&download_folder).exists() {
+        if let Err(e) = fs::remove_dir_all(&download_folder) {
+            return Err(format!("Failed to remove old {component} files: {e}"));
+        }
+    }
+
+    let mut files = Vec::new();
+    let mut links = Vec::new();
+    let mut total = 0;
 
-            let mut url = "";
+    if let Some(manifest_files) = manifest["files"].as_object() {
+        for (name, file) in manifest_files {
+            let path = format!("{}/{}", download_folder, name);
 
-            if let Some(versions) = p["versions"].as_array() {
-                for i in versions {
-                    if i["id"].as_str().unwrap() == version {
-                        url = i["url"].as_str().unwrap();
-                        break;
+            match file["type"].as_str() {
+                Some("directory") => {
+                    if let Err(e) = fs::create_dir_all(&path) {
+                        return Err(format!("Failed to create {path}: {e}"));
                     }
                 }
+                Some("file") => {
+                    let raw = &file["downloads"]["raw"];
+                    let size = raw["size"].as_u64().unwrap_or(0);
+                    total += size;
+
+                    files.push(JavaRuntimeFile {
+                        path,
+                        url: raw["url"].as_str().unwrap_or_default().to_owned(),
+                        sha1: raw["sha1"].as_str().unwrap_or_default().to_owned(),
+                        size,
+                        executable: file["executable"].as_bool().unwrap_or(false),
+                    });
+                }
+                Some("link") => {
+                    links.push((path, file["target"].as_str().unwrap_or_default().to_owned()));
+                }
+                _ => {}
             }
-            println!("Downloading json...");
-            let versionjson = reqwest::Client::new()
-                .get(url)
-                .send()
-                .await?
-                .bytes()
-                .await?;
+        }
+    }
 
-            let jfilelocation = format!("{}/{}.json", foldertosave, version);
-            fs::create_dir_all(foldertosave).unwrap();
-            let mut jfile = File::create(&jfilelocation).unwrap();
+    Ok(JavaRuntime {
+        folder,
+        download_folder,
+        version,
+        files,
+        links,
+        downloaded: 0,
+        total,
+        client,
+    })
+}
 
-            jfile.write_all(&versionjson).unwrap();
-            drop(jfile);
+struct AdoptiumPackage {
+    url: String,
+    checksum: String,
+    size: u64,
+    version: String,
+}
 
-            let mut jfile = File::open(jfilelocation).unwrap();
-            let mut fcontent = String::new();
-            jfile.read_to_string(&mut fcontent).unwrap();
-            let content = serde_json::from_str(&fcontent);
-            let json: Value = content.unwrap();
-            Ok(json)
+// Latest Temurin build of a Java major version for this system, a JDK is used when there's no JRE.
+async fn get_adoptium_package(client: &Client, java: u32) -> Result<AdoptiumPackage, String> {
+    let os = match std::env::consts::OS {
+        "windows" => "windows",
+        "linux" => "linux",
+        _ => return Err("System not supported.".to_owned()),
+    };
+    let arch = match std::env::consts::ARCH {
+        "x86_64" => "x64",
+        "x86" => "x32",
+        "aarch64" => "aarch64",
+        "arm" => "arm",
+        other => other,
+    };
+
+    for image_type in ["jre", "jdk"] {
+        let response = match client
+            .get(format!(
+                "https://api.adoptium.net/v3/assets/latest/{}/hotspot",
+                java
+            ))
+            .query(&[
+                ("architecture", arch),
+                ("image_type", image_type),
+                ("os", os),
+                ("vendor", "eclipse"),
+            ])
+            .send()
+            .await
+        {
+            Ok(ok) => ok.text().await.unwrap_or_default(),
+            Err(e) => return Err(format!("Failed to get Java {java} from Adoptium: {e}")),
+        };
+
+        let assets: Value = match serde_json::from_str(&response) {
+            Ok(ok) => ok,
+            Err(e) => return Err(format!("Failed to read Adoptium response: {e}")),
+        };
+
+        let package = &assets[0]["binary"]["package"];
+        if let (Some(url), Some(checksum)) =
+            (package["link"].as_str(), package["checksum"].as_str())
+        {
+            // "17.0.11+9" -> "17.0.11", same format as JAVA_VERSION in the release file
+            let version = assets[0]["version"]["openjdk_version"]
+                .as_str()
+                .unwrap_or_default()
+                .split(['+', '-'])
+                .next()
+                .unwrap_or_default()
+                .to_owned();
+
+            return Ok(AdoptiumPackage {
+                url: url.to_owned(),
+                checksum: checksum.to_lowercase(),
+                size: package["size"].as_u64().unwrap_or(0),
+                version,
+            });
         }
-        VersionType::Fabric => {
-            // fabric versions also need the vanilla json, so we are downloading it too.
+    }
 
-            // vanilla json
-            let versionlistjson = reqwest::Client::new()
-                .get("https://launchermeta.mojang.com/mc/game/version_manifest_v2.json")
-                .send()
-                .await?
-                .text()
-                .await?;
+    Err(format!("Java {java} is not available for {os} {arch}."))
+}
 
-            let content = serde_json::from_str(&versionlistjson);
+// Latest version of a runtime in minelander_java, e.g. java17 or java-runtime-gamma.
+pub async fn get_latest_runtime_version(runtime: String) -> Result<String, String> {
+    let client = super::network::client();
 
-            let p: Value = content.unwrap();
+    match super::java::get_launcher_java_major(&runtime) {
+        Some(java) => Ok(get_adoptium_package(&client, java).await?.version),
+        None => Ok(get_java_runtime_entry(&client, &runtime).await?.1),
+    }
+}
 
-            let mut url = "";
+pub fn get_sha1(bytes: &[u8]) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(bytes);
+    format!("{:x}", hasher.finalize())
+}
 
-            if let Some(versions) = p["versions"].as_array() {
-                for i in versions {
-                    if i["id"].as_str().unwrap() == version {
-                        url = i["url"].as_str().unwrap();
-                        break;
-                    }
-                }
-            }
+const VERSION_MANIFEST_URL: &str =
+    "https://launchermeta.mojang.com/mc/game/version_manifest_v2.json";
 
-            println!("Downloading json...");
-            let versionjson = reqwest::Client::new()
-                .get(url)
-                .send()
-                .await?
-                .bytes()
-                .await?;
+pub struct VersionManifest {
+    pub json: Value,
+    // why the cached manifest was used, None when it is up to date
+    pub offline_error: Option<String>,
+}
 
-            let jfilelocation = format!("{}/{}.json", foldertosave, version);
-            fs::create_dir_all(foldertosave).unwrap();
-            let mut jfile = File::create(jfilelocation).unwrap();
+// The version manifest is cached and revalidated with ETag/Last-Modified, the cache is used when offline.
+pub async fn get_version_manifest(client: &Client) -> Result<VersionManifest, String> {
+    let cache_dir = format!("{}/minelander_cache", super::launcher::get_minecraft_dir());
+    let manifest_path = format!("{}/version_manifest_v2.json", cache_dir);
+    let headers_path = format!("{}/version_manifest_v2.headers.json", cache_dir);
+
+    let cached_manifest: Option<Value> = fs::read_to_string(&manifest_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok());
+    let cached_headers: Value = fs::read_to_string(&headers_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+
+    let request = |url: &str| {
+        let mut request = client.get(url);
+        if cached_manifest.is_some() {
+            if let Some(etag) = cached_headers["etag"].as_str() {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = cached_headers["last_modified"].as_str() {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        request
+    };
 
-            jfile.write_all(&versionjson).unwrap();
+    let error = match super::network::send(VERSION_MANIFEST_URL, request).await {
+        Ok(response) if response.status() == StatusCode::NOT_MODIFIED => match cached_manifest {
+            Some(json) => {
+                return Ok(VersionManifest {
+                    json,
+                    offline_error: None,
+                })
+            }
+            None => String::from("the server sent no manifest"),
+        },
+        Ok(response) => match response.error_for_status() {
+            Ok(response) => {
+                let get_header = |name| {
+                    response
+                        .headers()
+                        .get(name)
+                        .and_then(|value: &HeaderValue| value.to_str().ok())
+                        .map(|value| value.to_owned())
+                };
+                let headers = serde_json::json!({
+                    "etag": get_header(ETAG),
+                    "last_modified": get_header(LAST_MODIFIED),
+                });
+
+                match response.text().await {
+                    Ok(content) => match serde_json::from_str(&content) {
+                        Ok(json) => {
+                            // without the cache the manifest is just downloaded again next time
+                            if let Err(e) = fs::create_dir_all(&cache_dir)
+                                .and_then(|_| fs::write(&manifest_path, &content))
+                                .and_then(|_| fs::write(&headers_path, headers.to_string()))
+                            {
+                                println!("Failed to cache version manifest: {e}")
+                            }
+
+                            return Ok(VersionManifest {
+                                json,
+                                offline_error: None,
+                            });
+                        }
+                        Err(e) => e.to_string(),
+                    },
+                    Err(e) => e.to_string(),
+                }
+            }
+            Err(e) => e.to_string(),
+        },
+        Err(e) => e.to_string(),
+    };
 
-            // fabric json
-            let fabricloaderlist = client
-                .get("https://meta.fabricmc.net/v2/versions/loader")
-                .send()
-                .await?
-                .text()
-                .await?;
+    match cached_manifest {
+        Some(json) => Ok(VersionManifest {
+            json,
+            offline_error: Some(error),
+        }),
+        None => Err(format!("failed to get version manifest: {}", error)),
+    }
+}
 
-            let content: Value = serde_json::from_str(&fabricloaderlist).unwrap();
+// Json file
+pub async fn downloadversionjson(
+    version_type: &VersionType,
+    version: &str,
+    foldertosave: &String,
+    client: &Client,
+) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+    match version_type {
+        // forge writes its own json after the vanilla one
+        VersionType::Vanilla | VersionType::Forge(_) | VersionType::NeoForge(_) => {
+            Ok(download_vanilla_json(version, foldertosave, client).await?)
+        }
+        VersionType::Fabric(_) | VersionType::Quilt(_) => {
+            // loader versions also need the vanilla json, so we are downloading it too.
 
-            let fabricloaderversion =
-                if let Some(first_object) = content.as_array().and_then(|arr| arr.first()) {
-                    first_object["version"].as_str().unwrap()
-                } else {
-                    panic!("Failed to get fabric loader name")
-                };
+            download_vanilla_json(version, foldertosave, client).await?;
 
-            let verjson = client
-                .get(format!(
-                    "https://meta.fabricmc.net/v2/versions/loader/{}/{}/profile/json",
-                    version, fabricloaderversion
-                ))
-                .send()
+            // loader json
+            let profile_url = version_type.get_profile_url(version).unwrap_or_default();
+            let verjson = super::network::send(&profile_url, |url| client.get(url))
                 .await?
+                .error_for_status()?
                 .bytes()
                 .await?;
 
-            let jfilelocation = format!("{}/{}-fabric.json", foldertosave, version);
+            let jfilelocation = format!(
+                "{}/{}.json",
+                foldertosave,
+                version_type.get_version_name(version)
+            );
             fs::create_dir_all(foldertosave).unwrap();
             let mut jfile = File::create(&jfilelocation).unwrap();
 
@@ -826,56 +2099,206 @@ pub async fn downloadversionjson(
     }
 }
 
-pub async fn get_downloadable_version_list(
-    showallversions: bool,
-) -> Result<Vec<Vec<String>>, String> {
-    let client = reqwest::Client::new();
-    // vanilla
-    let vanillaversionlistjson = match client
-        .get("https://launchermeta.mojang.com/mc/game/version_manifest_v2.json")
-        .send()
-        .await
-    {
-        Ok(ok) => match ok.text().await {
-            Ok(ok) => ok,
-            Err(e) => return Err(format!("failed to get download list: {}", e)),
-        },
-        Err(e) => return Err(format!("failed to get download list: {}", e)),
+// The manifest has the sha1 of each version json.
+async fn download_vanilla_json(
+    version: &str,
+    foldertosave: &str,
+    client: &Client,
+) -> Result<Value, String> {
+    let manifest = get_version_manifest(client).await?.json;
+    let Some(entry) = manifest["versions"]
+        .as_array()
+        .and_then(|versions| versions.iter().find(|i| i["id"].as_str() == Some(version)))
+    else {
+        return Err(format!("{version} is not in the version manifest"));
     };
 
-    let content = serde_json::from_str(&vanillaversionlistjson);
-
-    let p: Value = match content {
-        Ok(ok) => ok,
-        Err(e) => return Err(format!("failed to read list as json: {}", e)),
+    println!("Downloading json...");
+    let json = Download {
+        path: format!("{}/{}.json", foldertosave, version),
+        url: entry["url"].as_str().unwrap_or_default().to_string(),
+        sha1: entry["sha1"].as_str().map(|s| s.to_owned()),
+        size: None,
     };
+    download_file(client.clone(), json.clone(), Arc::default()).await?;
+    read_json(&json.path)
+}
+
+// A game version of the manifest, loader only versions have no release time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestVersion {
+    pub id: String,
+    // release, snapshot, old_beta or old_alpha
+    pub version_type: String,
+    pub release_time: String,
+}
+
+impl std::fmt::Display for ManifestVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // "2024-04-23T12:36:44+00:00" -> "2024-04-23"
+        match self.release_time.get(..10) {
+            Some(date) => write!(f, "{} ({})", self.id, date),
+            None => write!(f, "{}", self.id),
+        }
+    }
+}
+
+// Version types shown in the install list.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VersionFilter {
+    pub release: bool,
+    pub snapshot: bool,
+    pub old_beta: bool,
+    pub old_alpha: bool,
+}
+
+impl Default for VersionFilter {
+    fn default() -> Self {
+        VersionFilter {
+            release: true,
+            snapshot: false,
+            old_beta: false,
+            old_alpha: false,
+        }
+    }
+}
+
+impl VersionFilter {
+    pub fn filter(&self, versions: &[ManifestVersion], search: &str) -> Vec<ManifestVersion> {
+        let search = search.trim().to_lowercase();
+
+        versions
+            .iter()
+            .filter(|version| {
+                let type_shown = match version.version_type.as_str() {
+                    "release" => self.release,
+                    "old_beta" => self.old_beta,
+                    "old_alpha" => self.old_alpha,
+                    _ => self.snapshot,
+                };
+                type_shown && version.id.to_lowercase().contains(&search)
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DownloadableVersionList {
+    pub vanilla: Vec<ManifestVersion>,
+    pub fabric: Vec<ManifestVersion>,
+    pub quilt: Vec<ManifestVersion>,
+    pub forge: Vec<ManifestVersion>,
+    pub neoforge: Vec<ManifestVersion>,
+    // set when offline, only cached vanilla versions are listed then
+    pub offline_error: Option<String>,
+}
+
+pub async fn get_downloadable_version_list() -> Result<DownloadableVersionList, String> {
+    let client = super::network::client();
+    // vanilla
+    let manifest = get_version_manifest(&client).await?;
+    let p = manifest.json;
 
-    let mut vanillaversionlist: Vec<String> = vec![];
+    let mut vanillaversionlist: Vec<ManifestVersion> = vec![];
     if let Some(versions) = p["versions"].as_array() {
-        if showallversions {
-            for i in versions {
-                vanillaversionlist.push(i["id"].as_str().unwrap().to_owned())
-            }
-        } else {
-            for i in versions {
-                if i["type"] == "release" {
-                    vanillaversionlist.push(i["id"].as_str().unwrap().to_owned())
-                }
-            }
+        for i in versions {
+            vanillaversionlist.push(ManifestVersion {
+                id: i["id"].as_str().unwrap().to_owned(),
+                version_type: i["type"].as_str().unwrap_or_default().to_owned(),
+                release_time: i["releaseTime"].as_str().unwrap_or_default().to_owned(),
+            })
+        }
+    }
+    if manifest.offline_error.is_some() {
+        return Ok(DownloadableVersionList {
+            vanilla: vanillaversionlist,
+            fabric: vec![],
+            quilt: vec![],
+            forge: vec![],
+            neoforge: vec![],
+            offline_error: manifest.offline_error,
+        });
+    }
+
+    // loaders list their versions without metadata, it is taken from the manifest
+    let get_manifest_version =
+        |id: &str, stable: bool| match vanillaversionlist.iter().find(|version| version.id == id) {
+            Some(version) => version.clone(),
+            None => ManifestVersion {
+                id: id.to_owned(),
+                version_type: match stable {
+                    true => String::from("release"),
+                    false => String::from("snapshot"),
+                },
+                release_time: String::new(),
+            },
+        };
+
+    // loaders are best-effort, one that can't be reached is left empty
+    let fabricversionlist: Vec<ManifestVersion> =
+        get_loader_list("fabric", get_fabric_game_versions(&client).await)
+            .into_iter()
+            .map(|(id, stable)| get_manifest_version(&id, stable))
+            .collect();
+
+    let quiltversionlist: Vec<ManifestVersion> =
+        get_loader_list("quilt", get_quilt_game_versions(&client).await)
+            .into_iter()
+            .map(|(id, stable)| get_manifest_version(&id, stable))
+            .collect();
+
+    // forge, in the same order as the vanilla list
+    let forge_game_versions =
+        get_loader_list("forge", super::forge::get_forge_game_versions().await);
+    let forgeversionlist: Vec<ManifestVersion> = vanillaversionlist
+        .iter()
+        .filter(|version| forge_game_versions.contains(&version.id))
+        .cloned()
+        .collect();
+
+    // neoforge
+    let neoforge_game_versions =
+        get_loader_list("neoforge", super::forge::get_neoforge_game_versions().await);
+    let neoforgeversionlist: Vec<ManifestVersion> = vanillaversionlist
+        .iter()
+        .filter(|version| neoforge_game_versions.contains(&version.id))
+        .cloned()
+        .collect();
+
+    Ok(DownloadableVersionList {
+        vanilla: vanillaversionlist,
+        fabric: fabricversionlist,
+        quilt: quiltversionlist,
+        forge: forgeversionlist,
+        neoforge: neoforgeversionlist,
+        offline_error: None,
+    })
+}
+
+fn get_loader_list<T>(loader: &str, list: Result<Vec<T>, String>) -> Vec<T> {
+    match list {
+        Ok(ok) => ok,
+        Err(e) => {
+            println!("Failed to get the {loader} version list: {e}");
+            vec![]
         }
     }
-    // fabric
-    let fabricversionlistjson = match client
-        .get("https://meta.fabricmc.net/v2/versions/game")
-        .send()
+}
+
+// Game versions with their stable flag.
+async fn get_fabric_game_versions(client: &Client) -> Result<Vec<(String, bool)>, String> {
+    let fabric_list_url = "https://meta.fabricmc.net/v2/versions/game";
+    let fabricversionlistjson = match super::network::send(fabric_list_url, |url| client.get(url))
         .await
+        .and_then(|response| response.error_for_status())
     {
-        Ok(ok) => match ok.text().await {
-            Ok(ok) => ok,
+            Ok(ok) => match ok.text().await {
+                Ok(ok) => ok,
+                Err(e) => return Err(format!("failed to get fabric download list: {}", e)),
+            },
             Err(e) => return Err(format!("failed to get fabric download list: {}", e)),
-        },
-        Err(e) => return Err(format!("failed to get fabric download list: {}", e)),
-    };
+        };
 
     let content = serde_json::from_str(&fabricversionlistjson);
 
@@ -884,21 +2307,100 @@ pub async fn get_downloadable_version_list(
         Err(e) => return Err(format!("failed to read fabric list as json: {}", e)),
     };
 
-    let mut fabricversionlist: Vec<String> = vec![];
-    if let Some(versions) = p.as_array() {
-        if showallversions {
-            for i in versions {
-                fabricversionlist.push(i["version"].as_str().unwrap().to_owned())
+    Ok(get_game_versions(&p))
+}
+
+async fn get_quilt_game_versions(client: &Client) -> Result<Vec<(String, bool)>, String> {
+    let quilt_list_url = "https://meta.quiltmc.org/v3/versions/game";
+    let quiltversionlistjson = match super::network::send(quilt_list_url, |url| client.get(url))
+        .await
+        .and_then(|response| response.error_for_status())
+    {
+        Ok(ok) => match ok.json::<Value>().await {
+            Ok(ok) => ok,
+            Err(e) => return Err(format!("failed to read quilt list as json: {}", e)),
+        },
+        Err(e) => return Err(format!("failed to get quilt download list: {}", e)),
+    };
+
+    Ok(get_game_versions(&quiltversionlistjson))
+}
+
+// Fabric and Quilt list game versions as [{"version": "1.20.4", "stable": true}]
+fn get_game_versions(list: &Value) -> Vec<(String, bool)> {
+    match list.as_array() {
+        Some(versions) => versions
+            .iter()
+            .filter_map(|i| Some((i["version"].as_str()?.to_owned(), i["stable"] == true)))
+            .collect(),
+        None => vec![],
+    }
+}
+
+// Loader versions that support the game version, newest first.
+pub async fn get_fabric_loader_versions(
+    game_version: String,
+) -> Result<Vec<LoaderVersion>, String> {
+    let client = super::network::client();
+    let loaders_url = format!(
+        "https://meta.fabricmc.net/v2/versions/loader/{}",
+        game_version
+    );
+    let loaders = match super::network::send(&loaders_url, |url| client.get(url))
+        .await
+        .and_then(|response| response.error_for_status())
+    {
+        Ok(ok) => match ok.json::<Value>().await {
+            Ok(ok) => ok,
+            Err(e) => return Err(format!("failed to read fabric loader list: {}", e)),
+        },
+        Err(e) => return Err(format!("failed to get fabric loader list: {}", e)),
+    };
+
+    let mut loader_list = vec![];
+    if let Some(loaders) = loaders.as_array() {
+        for i in loaders {
+            if let Some(version) = i["loader"]["version"].as_str() {
+                loader_list.push(LoaderVersion {
+                    version: version.to_owned(),
+                    stable: i["loader"]["stable"].as_bool().unwrap_or(false),
+                })
             }
-        } else {
-            for i in versions {
-                if i["stable"] == true {
-                    fabricversionlist.push(i["version"].as_str().unwrap().to_owned())
-                }
+        }
+    }
+    Ok(loader_list)
+}
+
+// Quilt doesn't mark loaders as stable, betas have a suffix like 0.26.0-beta.1
+pub async fn get_quilt_loader_versions(game_version: String) -> Result<Vec<LoaderVersion>, String> {
+    let client = super::network::client();
+    let loaders_url = format!(
+        "https://meta.quiltmc.org/v3/versions/loader/{}",
+        game_version
+    );
+    let loaders = match super::network::send(&loaders_url, |url| client.get(url))
+        .await
+        .and_then(|response| response.error_for_status())
+    {
+        Ok(ok) => match ok.json::<Value>().await {
+            Ok(ok) => ok,
+            Err(e) => return Err(format!("failed to read quilt loader list: {}", e)),
+        },
+        Err(e) => return Err(format!("failed to get quilt loader list: {}", e)),
+    };
+
+    let mut loader_list = vec![];
+    if let Some(loaders) = loaders.as_array() {
+        for i in loaders {
+            if let Some(version) = i["loader"]["version"].as_str() {
+                loader_list.push(LoaderVersion {
+                    version: version.to_owned(),
+                    stable: !version.contains('-'),
+                })
             }
         }
     }
-    Ok(vec![vanillaversionlist, fabricversionlist])
+    Ok(loader_list)
 }
 
 pub fn get_libraries(
@@ -906,6 +2408,29 @@ pub fn get_libraries(
     libraries: &Vec<Value>,
     foldertosave: &String,
 ) -> Result<Vec<Download>, Box<dyn std::error::Error>> {
+    let library_download_list = get_library_downloads(mc_dir, libraries, foldertosave);
+    create_parent_dirs(&library_download_list)?;
+    Ok(library_download_list)
+}
+
+// Creates the folders the files go in, each folder once.
+fn create_parent_dirs(download_list: &[Download]) -> std::io::Result<()> {
+    let folders: HashSet<&Path> = download_list
+        .iter()
+        .filter_map(|file| Path::new(&file.path).parent())
+        .collect();
+    for folder in folders {
+        fs::create_dir_all(folder)?;
+    }
+    Ok(())
+}
+
+// Same as get_libraries without creating any folder.
+pub fn get_library_downloads(
+    mc_dir: &String,
+    libraries: &Vec<Value>,
+    foldertosave: &String,
+) -> Vec<Download> {
     //libraries and natives
     let lib_dir = format!("{}/libraries/", mc_dir);
     let os = std::env::consts::OS;
@@ -948,17 +2473,6 @@ pub fn get_libraries(
                         last_piece
                     );
 
-                    // create folder for lib
-                    match fs::create_dir_all(format!(
-                        "{}/{}/{}",
-                        lib_dir,
-                        &firstpiece,
-                        &lpieces.join("/")
-                    )) {
-                        Ok(ok) => ok,
-                        Err(err) => panic!("{err}"),
-                    };
-
                     let libpath = format!("{}{}", lib_dir, lib);
 
                     let unmodifiedurl = if !library["downloads"]["artifact"]["url"].is_null() {
@@ -970,30 +2484,19 @@ pub fn get_libraries(
                     };
 
                     let url = get_library_url(unmodifiedurl, lib);
-
-                    library_download_list.push(Download { path: libpath, url })
+                    let (sha1, size) =
+                        get_library_checksum(library, &library["downloads"]["artifact"]);
+
+                    library_download_list.push(Download {
+                        path: libpath,
+                        url,
+                        sha1,
+                        size,
+                    })
                 }
 
                 LibraryType::Normal => {
-                    let lib = format!(
-                        "{}/{}/{}-{}.jar",
-                        &firstpiece,
-                        &lpieces.join("/"),
-                        &lpieces[&lpieces.len() - 2],
-                        &lpieces[&lpieces.len() - 1]
-                    );
-
-                    // create folder for lib
-                    match fs::create_dir_all(format!(
-                        "{}/{}/{}",
-                        lib_dir,
-                        &firstpiece,
-                        &lpieces.join("/")
-                    )) {
-                        Ok(ok) => ok,
-                        Err(err) => panic!("{err}"),
-                    };
-
+                    let lib = super::launcher::get_library_path(libraryname);
                     let libpath = format!("{}{}", lib_dir, lib);
 
                     let unmodifiedurl = if !library["downloads"]["artifact"]["url"].is_null() {
@@ -1005,8 +2508,15 @@ pub fn get_libraries(
                     };
 
                     let url = get_library_url(unmodifiedurl, lib);
-
-                    library_download_list.push(Download { path: libpath, url })
+                    let (sha1, size) =
+                        get_library_checksum(library, &library["downloads"]["artifact"]);
+
+                    library_download_list.push(Download {
+                        path: libpath,
+                        url,
+                        sha1,
+                        size,
+                    })
                 }
 
                 LibraryType::Old => {
@@ -1019,17 +2529,6 @@ pub fn get_libraries(
                         os
                     );
 
-                    // create folder for lib
-                    match fs::create_dir_all(format!(
-                        "{}/{}/{}",
-                        lib_dir,
-                        &firstpiece,
-                        &lpieces.join("/")
-                    )) {
-                        Ok(ok) => ok,
-                        Err(err) => panic!("{err}"),
-                    };
-
                     let libpath = format!("{}{}", lib_dir, lib);
 
                     let unmodifiedurl = if !library["downloads"]["artifact"]["url"].is_null() {
@@ -1060,8 +2559,22 @@ pub fn get_libraries(
                     };
 
                     let url = get_library_url(unmodifiedurl, lib);
+                    let classifiers = &library["downloads"]["classifiers"];
+                    let classifier = match classifiers[format!("natives-{}", os)].is_null() {
+                        true => &classifiers[format!("natives-{}-64", os)],
+                        false => &classifiers[format!("natives-{}", os)],
+                    };
+                    let (sha1, size) = match library["downloads"]["artifact"]["url"].is_null() {
+                        true => get_library_checksum(library, classifier),
+                        false => get_library_checksum(library, &library["downloads"]["artifact"]),
+                    };
 
-                    library_download_list.push(Download { path: libpath, url })
+                    library_download_list.push(Download {
+                        path: libpath,
+                        url,
+                        sha1,
+                        size,
+                    })
                 }
             }
         }
@@ -1072,13 +2585,46 @@ pub fn get_libraries(
                 .unwrap()
                 .to_string();
 
-            fs::create_dir_all(format!("{}/natives", foldertosave)).unwrap();
-            let path = format!("{}/natives/natives.jar", foldertosave);
+            // each library gets its own jar, they are all extracted into the same folder
+            let artifact = library["name"].as_str().unwrap().split(':').nth(1);
+            let path = format!(
+                "{}/natives/{}-natives.jar",
+                foldertosave,
+                artifact.unwrap_or_default()
+            );
+            let (sha1, size) = get_library_checksum(
+                &Value::Null,
+                &library["downloads"]["classifiers"][format!("natives-{}", os)],
+            );
 
-            library_download_list.push(Download { path, url });
+            library_download_list.push(Download {
+                path,
+                url,
+                sha1,
+                size,
+            });
         }
     }
-    Ok(library_download_list)
+    library_download_list
+}
+
+// Natives are downloaded into the natives folder of the version and extracted there.
+pub fn is_natives_jar(path: &str) -> bool {
+    let in_natives_folder = Path::new(path)
+        .parent()
+        .is_some_and(|folder| folder.ends_with("natives"));
+    in_natives_folder && path.ends_with("-natives.jar")
+}
+
+// Mojang puts the hash in the download entry, some loaders put it in the library itself.
+fn get_library_checksum(library: &Value, download: &Value) -> (Option<String>, Option<u64>) {
+    let sha1 = download["sha1"]
+        .as_str()
+        .or(library["sha1"].as_str())
+        .map(|s| s.to_owned());
+    let size = download["size"].as_u64().or(library["size"].as_u64());
+
+    (sha1, size)
 }
 
 fn get_library_url(unmodifiedurl: &str, lib: String) -> String {
@@ -1092,6 +2638,15 @@ fn get_library_url(unmodifiedurl: &str, lib: String) -> String {
 }
 
 pub fn get_assets(mc_dir: &String, asset_index_json: Value) -> Result<Vec<Download>, String> {
+    let download_list = get_asset_downloads(mc_dir, &asset_index_json);
+    match create_parent_dirs(&download_list) {
+        Ok(_) => Ok(download_list),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// Same as get_assets without creating any folder.
+pub fn get_asset_downloads(mc_dir: &String, asset_index_json: &Value) -> Vec<Download> {
     let save_to_resources = !asset_index_json["map_to_resources"].is_null();
     let mut download_list = Vec::new();
 
@@ -1103,11 +2658,6 @@ pub fn get_assets(mc_dir: &String, asset_index_json: Value) -> Result<Vec<Downlo
             if let Some(hash) = value["hash"].as_str() {
                 match save_to_resources {
                     true => {
-                        match fs::create_dir_all(&old_assets_directory) {
-                            Ok(ok) => ok,
-                            Err(e) => return Err(e.to_string()),
-                        };
-
                         let asset_path = format!("{}/{}", old_assets_directory, key);
                         let asset_url = format!(
                             "https://resources.download.minecraft.net/{}/{}",
@@ -1118,14 +2668,12 @@ pub fn get_assets(mc_dir: &String, asset_index_json: Value) -> Result<Vec<Downlo
                         download_list.push(Download {
                             path: asset_path,
                             url: asset_url,
+                            sha1: Some(hash.to_owned()),
+                            size: value["size"].as_u64(),
                         });
                     }
 
                     false => {
-                        match fs::create_dir_all(format!("{}/{}", assets_directory, &hash[0..2])) {
-                            Ok(ok) => ok,
-                            Err(e) => return Err(e.to_string()),
-                        };
                         let asset_path = format!("{}/{}/{}", &assets_directory, &hash[0..2], &hash);
 
                         let asset_url = format!(
@@ -1137,6 +2685,8 @@ pub fn get_assets(mc_dir: &String, asset_index_json: Value) -> Result<Vec<Downlo
                         download_list.push(Download {
                             path: asset_path,
                             url: asset_url,
+                            sha1: Some(hash.to_owned()),
+                            size: value["size"].as_u64(),
                         });
                     }
                 }
@@ -1144,5 +2694,5 @@ pub fn get_assets(mc_dir: &String, asset_index_json: Value) -> Result<Vec<Downlo
         }
     }
 
-    Ok(download_list)
+    download_list
 }
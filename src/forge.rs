@@ -0,0 +1,613 @@
+use reqwest::Client;
+use serde_json::Value;
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufReader, Read},
+    path::Path,
+    process::Command,
+};
+use zip::ZipArchive;
+
+use super::{
+    downloader::{get_sha1, Download, LoaderVersion},
+    launcher::{get_classpath_separator, get_library_path, get_minecraft_dir},
+};
+
+const FORGE_MAVEN: &str = "https://maven.minecraftforge.net/";
+const NEOFORGE_MAVEN: &str = "https://maven.neoforged.net/releases/";
+
+// Both use the same installer format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ForgeLoader {
+    #[default]
+    Forge,
+    NeoForge,
+}
+
+impl ForgeLoader {
+    pub const ALL: [ForgeLoader; 2] = [ForgeLoader::Forge, ForgeLoader::NeoForge];
+
+    pub fn get_installer_url(&self, game_version: &str, version: &str) -> String {
+        match self {
+            ForgeLoader::Forge => {
+                let full_version = format!("{}-{}", game_version, version);
+                format!(
+                    "{}net/minecraftforge/forge/{}/forge-{}-installer.jar",
+                    FORGE_MAVEN, full_version, full_version
+                )
+            }
+            ForgeLoader::NeoForge => format!(
+                "{}net/neoforged/neoforge/{}/neoforge-{}-installer.jar",
+                NEOFORGE_MAVEN, version, version
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for ForgeLoader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ForgeLoader::Forge => write!(f, "Forge"),
+            ForgeLoader::NeoForge => write!(f, "NeoForge"),
+        }
+    }
+}
+
+// What is needed to finish the installation after the libraries are downloaded.
+pub struct ForgeInstall {
+    version_name: String,
+    game_version: String,
+    installer_path: String,
+    profile: Value,
+    java: String,
+}
+
+// Forge builds of every game version, e.g. "1.20.4": ["1.20.4-49.0.30", ...]
+async fn get_forge_metadata() -> Result<Value, String> {
+    match super::network::client()
+        .get(format!(
+            "{}net/minecraftforge/forge/maven-metadata.json",
+            FORGE_MAVEN
+        ))
+        .send()
+        .await
+    {
+        Ok(ok) => match ok.json::<Value>().await {
+            Ok(ok) => Ok(ok),
+            Err(e) => Err(format!("failed to read forge version list: {}", e)),
+        },
+        Err(e) => Err(format!("failed to get forge version list: {}", e)),
+    }
+}
+
+pub async fn get_forge_game_versions() -> Result<Vec<String>, String> {
+    let metadata = get_forge_metadata().await?;
+
+    Ok(match metadata.as_object() {
+        Some(versions) => versions.keys().cloned().collect(),
+        None => vec![],
+    })
+}
+
+// Every NeoForge version, e.g. 20.4.80-beta
+async fn get_neoforge_metadata() -> Result<Vec<String>, String> {
+    let metadata = match super::network::client()
+        .get("https://maven.neoforged.net/api/maven/versions/releases/net/neoforged/neoforge")
+        .send()
+        .await
+    {
+        Ok(ok) => match ok.json::<Value>().await {
+            Ok(ok) => ok,
+            Err(e) => return Err(format!("failed to read neoforge version list: {}", e)),
+        },
+        Err(e) => return Err(format!("failed to get neoforge version list: {}", e)),
+    };
+
+    Ok(match metadata["versions"].as_array() {
+        Some(versions) => versions
+            .iter()
+            .filter_map(|version| version.as_str().map(|s| s.to_owned()))
+            .collect(),
+        None => vec![],
+    })
+}
+
+// NeoForge versions start with the game version without "1.", 20.4.80 is for 1.20.4 and 21.0.1 for 1.21
+fn get_neoforge_game_version(neoforge_version: &str) -> Option<String> {
+    let mut pieces = neoforge_version.split('.');
+    let major = pieces.next()?.parse::<u32>().ok()?;
+    let minor = pieces.next()?.parse::<u32>().ok()?;
+
+    match minor {
+        0 => Some(format!("1.{}", major)),
+        _ => Some(format!("1.{}.{}", major, minor)),
+    }
+}
+
+pub async fn get_neoforge_game_versions() -> Result<Vec<String>, String> {
+    let mut game_versions: Vec<String> = get_neoforge_metadata()
+        .await?
+        .iter()
+        .filter_map(|version| get_neoforge_game_version(version))
+        .collect();
+    // the versions aren't sorted by game version
+    game_versions.sort_unstable();
+    game_versions.dedup();
+
+    Ok(game_versions)
+}
+
+// Versions for the game version without the game version prefix, newest first.
+pub async fn get_forge_versions(
+    loader: ForgeLoader,
+    game_version: String,
+) -> Result<Vec<LoaderVersion>, String> {
+    let mut forge_versions: Vec<String> = vec![];
+
+    match loader {
+        ForgeLoader::Forge => {
+            let metadata = get_forge_metadata().await?;
+
+            if let Some(versions) = metadata[&game_version].as_array() {
+                for i in versions {
+                    if let Some(version) = i.as_str() {
+                        let prefix = format!("{}-", game_version);
+                        forge_versions
+                            .push(version.strip_prefix(&prefix).unwrap_or(version).to_owned())
+                    }
+                }
+            }
+        }
+        ForgeLoader::NeoForge => {
+            for version in get_neoforge_metadata().await? {
+                if get_neoforge_game_version(&version).as_ref() == Some(&game_version) {
+                    forge_versions.push(version)
+                }
+            }
+        }
+    }
+    forge_versions.sort_by(|a, b| compare_versions(b, a));
+
+    Ok(forge_versions
+        .into_iter()
+        .map(|version| LoaderVersion {
+            stable: !version.contains("beta"),
+            version,
+        })
+        .collect())
+}
+
+// Compares numbers as numbers, so 49.0.10 is newer than 49.0.9
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let a_pieces = a.split(['.', '-']);
+    let mut b_pieces = b.split(['.', '-']);
+
+    for a_piece in a_pieces {
+        let Some(b_piece) = b_pieces.next() else {
+            return Ordering::Greater;
+        };
+
+        let ordering = match (a_piece.parse::<u64>(), b_piece.parse::<u64>()) {
+            (Ok(a_number), Ok(b_number)) => a_number.cmp(&b_number),
+            _ => a_piece.cmp(b_piece),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    match b_pieces.next() {
+        Some(_) => Ordering::Less,
+        None => Ordering::Equal,
+    }
+}
+
+// Downloads the installer, writes the version json and returns the libraries to download.
+pub async fn prepare(
+    client: &Client,
+    installer_url: String,
+    game_version: &str,
+    version_name: &str,
+    java: String,
+) -> Result<(ForgeInstall, Vec<Download>), String> {
+    let mc_dir = get_minecraft_dir();
+    let lib_dir = format!("{}/libraries", mc_dir);
+
+    let installer_folder = format!("{}/minelander_forge", mc_dir);
+    let installer_path = format!("{}/{}-installer.jar", installer_folder, version_name);
+
+    let installer = match client.get(installer_url).send().await {
+        Ok(ok) => match ok.error_for_status() {
+            Ok(ok) => match ok.bytes().await {
+                Ok(ok) => ok,
+                Err(e) => return Err(format!("failed to download forge installer: {}", e)),
+            },
+            Err(e) => return Err(format!("failed to download forge installer: {}", e)),
+        },
+        Err(e) => return Err(format!("failed to download forge installer: {}", e)),
+    };
+    if let Err(e) = fs::create_dir_all(&installer_folder) {
+        return Err(e.to_string());
+    }
+    if let Err(e) = fs::write(&installer_path, installer) {
+        return Err(e.to_string());
+    }
+
+    let mut installer = open_zip(&installer_path)?;
+    let profile = read_zip_json(&mut installer, "install_profile.json")?;
+
+    let (mut version_json, libraries) = if profile["install"].is_object() {
+        // installers older than 1.13 keep everything in install_profile.json
+        let universal_path = format!(
+            "{}/{}",
+            lib_dir,
+            get_library_path(profile["install"]["path"].as_str().unwrap_or_default())
+        );
+        extract_zip_file(
+            &mut installer,
+            profile["install"]["filePath"].as_str().unwrap_or_default(),
+            &universal_path,
+        )?;
+
+        let version_json = profile["versionInfo"].clone();
+        let libraries = version_json["libraries"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        (version_json, libraries)
+    } else {
+        let json_name = profile["json"].as_str().unwrap_or("/version.json");
+        let version_json = read_zip_json(&mut installer, json_name.trim_start_matches('/'))?;
+
+        // the processors need their own libraries too
+        let mut libraries = version_json["libraries"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        libraries.extend(profile["libraries"].as_array().cloned().unwrap_or_default());
+        (version_json, libraries)
+    };
+
+    version_json["id"] = Value::String(version_name.to_owned());
+    if version_json["inheritsFrom"].is_null() {
+        version_json["inheritsFrom"] = Value::String(game_version.to_owned());
+    }
+
+    let version_folder = format!("{}/versions/{}", mc_dir, version_name);
+    if let Err(e) = fs::create_dir_all(&version_folder) {
+        return Err(e.to_string());
+    }
+    let json = match serde_json::to_string_pretty(&version_json) {
+        Ok(ok) => ok,
+        Err(e) => return Err(e.to_string()),
+    };
+    if let Err(e) = fs::write(format!("{}/{}.json", version_folder, version_name), json) {
+        return Err(e.to_string());
+    }
+
+    let mut download_list = vec![];
+    for library in &libraries {
+        let artifact = &library["downloads"]["artifact"];
+        let path = match artifact["path"].as_str() {
+            Some(path) => path.to_owned(),
+            None => get_library_path(library["name"].as_str().unwrap_or_default()),
+        };
+        let full_path = format!("{}/{}", lib_dir, path);
+
+        // some libraries are shipped inside the installer
+        if extract_zip_file(&mut installer, &format!("maven/{}", path), &full_path).is_ok() {
+            continue;
+        }
+
+        let url = match (artifact["url"].as_str(), library["url"].as_str()) {
+            (Some(url), _) => url.to_owned(),
+            (None, Some(base_url)) => format!(
+                "{}/{}",
+                base_url
+                    .replace("files.minecraftforge.net/maven", "maven.minecraftforge.net")
+                    .trim_end_matches('/'),
+                path
+            ),
+            (None, None) => format!("https://libraries.minecraft.net/{}", path),
+        };
+
+        // made by the processors
+        if url.is_empty() {
+            continue;
+        }
+
+        download_list.push(Download {
+            path: full_path,
+            url,
+            sha1: artifact["sha1"].as_str().map(|s| s.to_owned()),
+            size: artifact["size"].as_u64(),
+        })
+    }
+
+    Ok((
+        ForgeInstall {
+            version_name: version_name.to_owned(),
+            game_version: game_version.to_owned(),
+            installer_path,
+            profile,
+            java,
+        },
+        download_list,
+    ))
+}
+
+// Runs the installer processors, they patch the game jar and make the forge client libraries.
+pub fn run_processors(install: &ForgeInstall) -> Result<(), String> {
+    let Some(processors) = install.profile["processors"].as_array() else {
+        return Ok(());
+    };
+
+    let mc_dir = get_minecraft_dir();
+    let lib_dir = format!("{}/libraries", mc_dir);
+    let data_folder = format!("{}/minelander_forge/{}", mc_dir, install.version_name);
+    let mut installer = open_zip(&install.installer_path)?;
+
+    let mut data: HashMap<String, String> = HashMap::new();
+    data.insert("SIDE".to_owned(), "client".to_owned());
+    data.insert(
+        "MINECRAFT_JAR".to_owned(),
+        format!(
+            "{}/versions/{}/{}.jar",
+            mc_dir, install.version_name, install.version_name
+        ),
+    );
+    data.insert("MINECRAFT_VERSION".to_owned(), install.game_version.clone());
+    data.insert("ROOT".to_owned(), mc_dir.clone());
+    data.insert("INSTALLER".to_owned(), install.installer_path.clone());
+    data.insert("LIBRARY_DIR".to_owned(), lib_dir.clone());
+
+    if let Some(entries) = install.profile["data"].as_object() {
+        for (key, value) in entries {
+            let value = value["client"].as_str().unwrap_or_default();
+
+            let value =
+                if let Some(name) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+                    format!("{}/{}", lib_dir, get_library_path(name))
+                } else if let Some(literal) =
+                    value.strip_prefix('\'').and_then(|v| v.strip_suffix('\''))
+                {
+                    literal.to_owned()
+                } else if value.starts_with('/') {
+                    // files inside the installer
+                    let path = format!("{}{}", data_folder, value);
+                    extract_zip_file(&mut installer, value.trim_start_matches('/'), &path)?;
+                    path
+                } else {
+                    value.to_owned()
+                };
+
+            data.insert(key.to_owned(), value);
+        }
+    }
+
+    let resolve = |arg: &str| -> String {
+        if let Some(key) = arg.strip_prefix('{').and_then(|a| a.strip_suffix('}')) {
+            data.get(key).cloned().unwrap_or(arg.to_owned())
+        } else if let Some(name) = arg.strip_prefix('[').and_then(|a| a.strip_suffix(']')) {
+            format!("{}/{}", lib_dir, get_library_path(name))
+        } else {
+            arg.to_owned()
+        }
+    };
+
+    for processor in processors {
+        if let Some(sides) = processor["sides"].as_array() {
+            if !sides.iter().any(|side| side == "client") {
+                continue;
+            }
+        }
+
+        let outputs: Vec<(String, String)> = match processor["outputs"].as_object() {
+            Some(outputs) => outputs
+                .iter()
+                .map(|(path, sha1)| (resolve(path), resolve(sha1.as_str().unwrap_or_default())))
+                .collect(),
+            None => vec![],
+        };
+
+        // already done by an earlier installation
+        if !outputs.is_empty() && outputs.iter().all(|(path, sha1)| check_sha1(path, sha1)) {
+            continue;
+        }
+
+        let jar_name = processor["jar"].as_str().unwrap_or_default();
+        let jar = format!("{}/{}", lib_dir, get_library_path(jar_name));
+        let main_class = get_main_class(&jar)?;
+
+        let mut classpath = vec![jar];
+        if let Some(libraries) = processor["classpath"].as_array() {
+            for i in libraries {
+                classpath.push(format!(
+                    "{}/{}",
+                    lib_dir,
+                    get_library_path(i.as_str().unwrap_or_default())
+                ))
+            }
+        }
+
+        let args: Vec<String> = match processor["args"].as_array() {
+            Some(args) => args
+                .iter()
+                .map(|arg| resolve(arg.as_str().unwrap_or_default()))
+                .collect(),
+            None => vec![],
+        };
+
+        println!("Running Forge processor {}", jar_name);
+        let output = match Command::new(&install.java)
+            .args(super::network::get_java_proxy_args())
+            .arg("-cp")
+            .arg(classpath.join(&get_classpath_separator().to_string()))
+            .arg(&main_class)
+            .args(&args)
+            .output()
+        {
+            Ok(ok) => ok,
+            Err(e) => return Err(format!("Failed to run {}: {}", install.java, e)),
+        };
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!(
+                "Forge processor {} failed: {}",
+                jar_name,
+                stderr.lines().last().unwrap_or_default()
+            ));
+        }
+
+        for (path, sha1) in &outputs {
+            if !check_sha1(path, sha1) {
+                return Err(format!(
+                    "Forge processor {} made a corrupt {}",
+                    jar_name, path
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Library paths the installer of a version downloaded or made, the processor outputs aren't in the version json.
+pub fn get_installer_libraries(version_name: &str) -> Result<Vec<String>, String> {
+    let installer_path = format!(
+        "{}/minelander_forge/{}-installer.jar",
+        get_minecraft_dir(),
+        version_name
+    );
+    let profile = read_zip_json(&mut open_zip(&installer_path)?, "install_profile.json")?;
+
+    let mut names = vec![];
+    if let Some(path) = profile["install"]["path"].as_str() {
+        names.push(path.to_owned());
+    }
+    if let Some(entries) = profile["data"].as_object() {
+        for value in entries.values() {
+            let value = value["client"].as_str().unwrap_or_default();
+            if let Some(name) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+                names.push(name.to_owned());
+            }
+        }
+    }
+    for processor in profile["processors"].as_array().into_iter().flatten() {
+        names.extend(processor["jar"].as_str().map(|jar| jar.to_owned()));
+        for i in processor["classpath"].as_array().into_iter().flatten() {
+            names.extend(i.as_str().map(|name| name.to_owned()));
+        }
+        for arg in processor["args"].as_array().into_iter().flatten() {
+            let arg = arg.as_str().unwrap_or_default();
+            if let Some(name) = arg.strip_prefix('[').and_then(|a| a.strip_suffix(']')) {
+                names.push(name.to_owned());
+            }
+        }
+    }
+
+    let mut paths: Vec<String> = names.iter().map(|name| get_library_path(name)).collect();
+    for library in profile["libraries"].as_array().into_iter().flatten() {
+        match library["downloads"]["artifact"]["path"].as_str() {
+            Some(path) => paths.push(path.to_owned()),
+            None => paths.push(get_library_path(
+                library["name"].as_str().unwrap_or_default(),
+            )),
+        }
+    }
+
+    Ok(paths)
+}
+
+fn check_sha1(path: &str, sha1: &str) -> bool {
+    match fs::read(path) {
+        Ok(bytes) => get_sha1(&bytes) == sha1,
+        Err(_) => false,
+    }
+}
+
+fn get_main_class(jar: &str) -> Result<String, String> {
+    let mut jar_file = open_zip(jar)?;
+    let mut manifest = String::new();
+
+    match jar_file.by_name("META-INF/MANIFEST.MF") {
+        Ok(mut ok) => {
+            if let Err(e) = ok.read_to_string(&mut manifest) {
+                return Err(e.to_string());
+            }
+        }
+        Err(e) => return Err(format!("Failed to read manifest of {}: {}", jar, e)),
+    }
+
+    match manifest
+        .lines()
+        .find_map(|line| line.strip_prefix("Main-Class:"))
+    {
+        Some(main_class) => Ok(main_class.trim().to_owned()),
+        None => Err(format!("{} has no main class", jar)),
+    }
+}
+
+fn open_zip(path: &str) -> Result<ZipArchive<BufReader<File>>, String> {
+    let file = match File::open(path) {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("Failed to open {}: {}", path, e)),
+    };
+
+    match ZipArchive::new(BufReader::new(file)) {
+        Ok(ok) => Ok(ok),
+        Err(e) => Err(format!("Failed to read {}: {}", path, e)),
+    }
+}
+
+fn read_zip_json(zip: &mut ZipArchive<BufReader<File>>, name: &str) -> Result<Value, String> {
+    let mut content = String::new();
+
+    match zip.by_name(name) {
+        Ok(mut ok) => {
+            if let Err(e) = ok.read_to_string(&mut content) {
+                return Err(e.to_string());
+            }
+        }
+        Err(e) => {
+            return Err(format!(
+                "Failed to read {} from forge installer: {}",
+                name, e
+            ))
+        }
+    }
+
+    match serde_json::from_str(&content) {
+        Ok(ok) => Ok(ok),
+        Err(e) => Err(format!("Failed to read {} as json: {}", name, e)),
+    }
+}
+
+fn extract_zip_file(
+    zip: &mut ZipArchive<BufReader<File>>,
+    name: &str,
+    destination: &str,
+) -> Result<(), String> {
+    let mut file = match zip.by_name(name) {
+        Ok(ok) => ok,
+        Err(e) => return Err(e.to_string()),
+    };
+
+    if let Some(folder) = Path::new(destination).parent() {
+        if let Err(e) = fs::create_dir_all(folder) {
+            return Err(e.to_string());
+        }
+    }
+
+    let mut output = match File::create(destination) {
+        Ok(ok) => ok,
+        Err(e) => return Err(e.to_string()),
+    };
+    match std::io::copy(&mut file, &mut output) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
+}
@@ -0,0 +1,418 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::launcher::get_minecraft_dir;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct JavaInfo {
+    pub path: String,
+    pub version: String,
+    pub major_version: u32,
+    pub vendor: String,
+    pub arch: String,
+}
+
+// Named JVM arguments, stored in the settings file. max_java 0 means no maximum.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct JvmPreset {
+    pub name: String,
+    pub args: String,
+    pub min_java: u32,
+    pub max_java: u32,
+}
+
+impl JvmPreset {
+    pub fn supports(&self, java: u32) -> bool {
+        java >= self.min_java && (self.max_java == 0 || java <= self.max_java)
+    }
+}
+
+// Presets added to the settings file on first use, "Automatic" picks the first one that supports the Java version.
+pub fn get_default_jvm_presets() -> Vec<JvmPreset> {
+    let preset = |name: &str, args: &str, min_java, max_java| JvmPreset {
+        name: name.to_owned(),
+        args: args.to_owned(),
+        min_java,
+        max_java,
+    };
+
+    vec![
+        preset("Minelander", "-XX:+UnlockExperimentalVMOptions -XX:+UnlockDiagnosticVMOptions -XX:+AlwaysActAsServerClassMachine -XX:+AlwaysPreTouch -XX:+DisableExplicitGC -XX:+UseNUMA -XX:NmethodSweepActivity=1 -XX:ReservedCodeCacheSize=400M -XX:NonNMethodCodeHeapSize=12M -XX:ProfiledCodeHeapSize=194M -XX:NonProfiledCodeHeapSize=194M -XX:-DontCompileHugeMethods -XX:MaxNodeLimit=240000 -XX:NodeLimitFudgeFactor=8000 -XX:+UseVectorCmov -XX:+PerfDisableSharedMem -XX:+UseFastUnorderedTimeStamps -XX:+UseCriticalJavaThreadPriority -XX:ThreadPriorityPolicy=1 -XX:AllocatePrefetchStyle=3", 9, 0),
+        // AggressiveOpts was removed after Java 8
+        preset("Minelander (Java 8)", "-XX:+UnlockExperimentalVMOptions -XX:+UnlockDiagnosticVMOptions -XX:+AlwaysActAsServerClassMachine -XX:+ParallelRefProcEnabled -XX:+DisableExplicitGC -XX:+AlwaysPreTouch -XX:+AggressiveOpts -XX:MaxInlineLevel=15 -XX:MaxVectorSize=32 -XX:ThreadPriorityPolicy=1 -XX:+UseNUMA -XX:+UseDynamicNumberOfGCThreads -XX:NmethodSweepActivity=1 -XX:ReservedCodeCacheSize=350M -XX:-DontCompileHugeMethods -XX:MaxNodeLimit=240000 -XX:NodeLimitFudgeFactor=8000 -Dgraal.CompilerConfiguration=community", 0, 8),
+        preset("G1 default", "-XX:+UseG1GC", 0, 0),
+        preset("Aikar's flags", "-XX:+UseG1GC -XX:+ParallelRefProcEnabled -XX:MaxGCPauseMillis=200 -XX:+UnlockExperimentalVMOptions -XX:+DisableExplicitGC -XX:+AlwaysPreTouch -XX:G1NewSizePercent=30 -XX:G1MaxNewSizePercent=40 -XX:G1HeapRegionSize=8M -XX:G1ReservePercent=20 -XX:G1HeapWastePercent=5 -XX:G1MixedGCCountTarget=4 -XX:InitiatingHeapOccupancyPercent=15 -XX:G1MixedGCLiveThresholdPercent=90 -XX:G1RSetUpdatingPauseTimePercent=5 -XX:SurvivorRatio=32 -XX:+PerfDisableSharedMem -XX:MaxTenuringThreshold=1", 8, 0),
+        preset("ZGC generational", "-XX:+UseZGC -XX:+ZGenerational", 21, 0),
+        preset("Shenandoah", "-XX:+UseShenandoahGC", 12, 0),
+        preset("Minimal", "", 0, 0),
+    ]
+}
+
+// Runs the JVM and reads its version, vendor and architecture from the printed properties.
+pub fn probe(java_path: &str) -> Result<JavaInfo, String> {
+    // javaw doesn't print anything, so the java binary next to it is used instead
+    let executable = if java_path.ends_with("javaw.exe") {
+        let java = java_path.replace("javaw.exe", "java.exe");
+        match Path::new(&java).exists() {
+            true => java,
+            false => java_path.to_owned(),
+        }
+    } else {
+        java_path.to_owned()
+    };
+
+    let output = match Command::new(&executable)
+        .args(["-XshowSettings:properties", "-version"])
+        .output()
+    {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("Failed to run {}: {}", executable, e)),
+    };
+
+    // properties are printed to stderr
+    let properties = String::from_utf8_lossy(&output.stderr);
+
+    let mut info = JavaInfo {
+        path: java_path.to_owned(),
+        ..Default::default()
+    };
+
+    for line in properties.lines() {
+        if let Some((key, value)) = line.trim().split_once(" = ") {
+            match key {
+                "java.version" => info.version = value.to_owned(),
+                "java.vendor" => info.vendor = value.to_owned(),
+                "os.arch" => info.arch = value.to_owned(),
+                _ => {}
+            }
+        }
+    }
+
+    if info.version.is_empty() {
+        return Err(format!("Failed to read Java version of {}", java_path));
+    }
+
+    info.major_version = get_major_version(&info.version);
+    Ok(info)
+}
+
+// "1.8.0_412" -> 8, "17.0.9" -> 17
+pub fn get_major_version(version: &str) -> u32 {
+    let mut pieces = version.split(|c: char| !c.is_ascii_digit());
+
+    match pieces.next().and_then(|p| p.parse::<u32>().ok()) {
+        Some(1) => pieces.next().and_then(|p| p.parse().ok()).unwrap_or(1),
+        Some(major) => major,
+        None => 0,
+    }
+}
+
+// Looks for Java runtimes in the usual install locations and probes each one found.
+pub fn scan() -> Vec<JavaInfo> {
+    let mut java_paths: Vec<PathBuf> = Vec::new();
+    let java_executable = match std::env::consts::OS {
+        "windows" => "javaw.exe",
+        _ => "java",
+    };
+
+    let mut java_homes: Vec<PathBuf> = Vec::new();
+
+    if let Ok(java_home) = env::var("JAVA_HOME") {
+        java_homes.push(PathBuf::from(java_home));
+    }
+
+    match std::env::consts::OS {
+        "windows" => {
+            java_homes.extend(get_registry_java_homes());
+
+            for program_files in ["ProgramFiles", "ProgramW6432", "ProgramFiles(x86)"] {
+                if let Ok(program_files) = env::var(program_files) {
+                    for vendor in [
+                        "Java",
+                        "Eclipse Adoptium",
+                        "Eclipse Foundation",
+                        "AdoptOpenJDK",
+                        "Microsoft",
+                        "Zulu",
+                        "Amazon Corretto",
+                        "BellSoft",
+                    ] {
+                        let vendor_dir = Path::new(&program_files).join(vendor);
+                        java_homes.extend(get_subdirectories(&vendor_dir));
+                    }
+                }
+            }
+        }
+        _ => {
+            java_homes.extend(get_subdirectories(Path::new("/usr/lib/jvm")));
+            java_homes.extend(get_subdirectories(Path::new("/usr/lib64/jvm")));
+
+            if let Some(home) = env::var_os("HOME") {
+                java_homes.extend(get_subdirectories(
+                    &Path::new(&home).join(".sdkman/candidates/java"),
+                ));
+            }
+        }
+    }
+
+    for java_home in java_homes {
+        java_paths.push(java_home.join("bin").join(java_executable));
+    }
+
+    if let Some(path) = env::var_os("PATH") {
+        for dir in env::split_paths(&path) {
+            java_paths.push(dir.join(java_executable));
+        }
+    }
+
+    // the same runtime is usually reachable from more than one place (symlinks, JAVA_HOME, PATH)
+    let mut seen_paths = Vec::new();
+    let mut runtimes = Vec::new();
+
+    for java_path in java_paths {
+        let real_path = match fs::canonicalize(&java_path) {
+            Ok(ok) => ok,
+            Err(_) => continue,
+        };
+
+        // runtimes downloaded by Minelander already have their own entries
+        if real_path.to_string_lossy().contains("minelander_java")
+            || seen_paths.contains(&real_path)
+        {
+            continue;
+        }
+        seen_paths.push(real_path.clone());
+
+        match probe(&real_path.to_string_lossy()) {
+            Ok(info) => runtimes.push(info),
+            Err(e) => println!("{e}, ignoring."),
+        }
+    }
+
+    runtimes
+}
+
+// Name shown in the Java list for a detected runtime.
+pub fn get_display_name(info: &JavaInfo) -> String {
+    format!("Java {} ({}, {})", info.version, info.vendor, info.arch)
+}
+
+// Matches a saved name after a patch update, e.g. "Java 17.0.9 (Eclipse Adoptium, amd64)" and 17.0.10.
+pub fn is_same_runtime(info: &JavaInfo, name: &str) -> bool {
+    let Some((version, vendor_and_arch)) = name
+        .strip_prefix("Java ")
+        .and_then(|name| name.split_once(" ("))
+    else {
+        return false;
+    };
+
+    get_major_version(version) == info.major_version
+        && vendor_and_arch == format!("{}, {})", info.vendor, info.arch)
+}
+
+// Compares the os.arch reported by a JVM with the architecture Minelander was built for.
+pub fn is_native_arch(arch: &str) -> bool {
+    let arch = match arch {
+        "amd64" | "x86_64" => "x86_64",
+        "aarch64" | "arm64" => "aarch64",
+        "x86" | "i386" | "i686" => "x86",
+        other => other,
+    };
+
+    arch == std::env::consts::ARCH
+}
+
+// Platform name used by Mojang's java-runtime manifest, None if the system isn't listed there.
+pub fn get_runtime_platform() -> Option<&'static str> {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("linux", "x86_64") => Some("linux"),
+        ("linux", "x86") => Some("linux-i386"),
+        ("windows", "x86_64") => Some("windows-x64"),
+        ("windows", "x86") => Some("windows-x86"),
+        ("windows", "aarch64") => Some("windows-arm64"),
+        _ => None,
+    }
+}
+
+// Runtimes downloaded from Adoptium are installed to minelander_java/java<major version>.
+pub fn get_launcher_java_dir(java: u32) -> String {
+    format!("{}/minelander_java/java{}", get_minecraft_dir(), java)
+}
+
+pub fn get_launcher_java_path(java: u32) -> String {
+    match std::env::consts::OS {
+        "windows" => format!("{}/bin/javaw.exe", get_launcher_java_dir(java)),
+        _ => format!("{}/bin/java", get_launcher_java_dir(java)),
+    }
+}
+
+// "java17" -> 17, Mojang runtimes like "java-runtime-gamma" -> None
+pub fn get_launcher_java_major(runtime: &str) -> Option<u32> {
+    runtime.strip_prefix("java")?.parse().ok()
+}
+
+// Major versions of the Adoptium runtimes downloaded by Minelander.
+pub fn get_installed_launcher_java() -> Vec<u32> {
+    let java_dir = format!("{}/minelander_java", get_minecraft_dir());
+
+    let mut installed_java: Vec<u32> = get_subdirectories(Path::new(&java_dir))
+        .iter()
+        .filter_map(|dir| get_launcher_java_major(&dir.file_name()?.to_string_lossy()))
+        .collect();
+    installed_java.sort();
+
+    installed_java
+}
+
+// Runtimes from Mojang's manifest are installed to minelander_java/<component>, e.g. java-runtime-gamma.
+pub fn get_runtime_dir(component: &str) -> String {
+    format!("{}/minelander_java/{}", get_minecraft_dir(), component)
+}
+
+pub fn get_runtime_java_path(component: &str) -> String {
+    match std::env::consts::OS {
+        "windows" => format!("{}/bin/javaw.exe", get_runtime_dir(component)),
+        _ => format!("{}/bin/java", get_runtime_dir(component)),
+    }
+}
+
+// The .version file is written last, so a runtime without it was not fully installed.
+pub fn is_runtime_installed(component: &str) -> bool {
+    Path::new(&format!("{}/.version", get_runtime_dir(component))).exists()
+}
+
+// A runtime in minelander_java, downloaded from Adoptium or Mojang.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LauncherRuntime {
+    pub name: String,
+    pub version: String,
+    pub size: u64,
+    // the selected Java launches with it, the selection is shared by every instance
+    pub current: bool,
+    // installed versions that use it with Automatic
+    pub automatic_for: Vec<String>,
+}
+
+// Lists the runtimes downloaded by Minelander, which one is selected and the versions using them with Automatic.
+pub fn get_launcher_runtimes(
+    versions: Vec<String>,
+    selected_runtime: Option<String>,
+) -> Vec<LauncherRuntime> {
+    let java_dir = format!("{}/minelander_java", get_minecraft_dir());
+
+    let version_runtimes: Vec<(String, String)> = versions
+        .into_iter()
+        .filter_map(|version| {
+            let runtime = super::launcher::get_version_automatic_runtime(&version)?;
+            Some((version, runtime))
+        })
+        .collect();
+
+    let mut runtimes: Vec<LauncherRuntime> = get_subdirectories(Path::new(&java_dir))
+        .iter()
+        .map(|dir| {
+            let name = dir
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+
+            let automatic_for = version_runtimes
+                .iter()
+                .filter(|(_, runtime)| *runtime == name)
+                .map(|(version, _)| version.clone())
+                .collect();
+
+            LauncherRuntime {
+                version: get_runtime_version(dir),
+                size: get_dir_size(dir),
+                current: selected_runtime.as_ref() == Some(&name),
+                automatic_for,
+                name,
+            }
+        })
+        .collect();
+    runtimes.sort_by(|a, b| a.name.cmp(&b.name));
+
+    runtimes
+}
+
+// Mojang runtimes have a .version file written by Minelander, Adoptium ones have the JDK release file.
+pub fn get_runtime_version(dir: &Path) -> String {
+    if let Ok(version) = fs::read_to_string(dir.join(".version")) {
+        return version.trim().to_owned();
+    }
+
+    if let Ok(release) = fs::read_to_string(dir.join("release")) {
+        for line in release.lines() {
+            if let Some(version) = line.strip_prefix("JAVA_VERSION=") {
+                return version.trim_matches('"').to_owned();
+            }
+        }
+    }
+
+    String::from("unknown")
+}
+
+fn get_dir_size(dir: &Path) -> u64 {
+    let mut size = 0;
+
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            match entry.metadata() {
+                Ok(metadata) if metadata.is_dir() => size += get_dir_size(&entry.path()),
+                Ok(metadata) => size += metadata.len(),
+                Err(_) => {}
+            }
+        }
+    }
+
+    size
+}
+
+fn get_subdirectories(dir: &Path) -> Vec<PathBuf> {
+    match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn get_registry_java_homes() -> Vec<PathBuf> {
+    let mut java_homes = Vec::new();
+
+    for key in [
+        "HKLM\\SOFTWARE\\JavaSoft",
+        "HKLM\\SOFTWARE\\Eclipse Adoptium",
+        "HKLM\\SOFTWARE\\Eclipse Foundation",
+        "HKLM\\SOFTWARE\\AdoptOpenJDK",
+        "HKLM\\SOFTWARE\\Microsoft\\JDK",
+        "HKLM\\SOFTWARE\\Azul Systems\\Zulu",
+    ] {
+        let output = match Command::new("reg").args(["query", key, "/s"]).output() {
+            Ok(ok) => ok,
+            Err(_) => continue,
+        };
+
+        // lines look like "    JavaHome    REG_SZ    C:\Program Files\Java\jre-1.8"
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let line = line.trim();
+            if !(line.starts_with("JavaHome") || line.starts_with("Path")) {
+                continue;
+            }
+            if let Some((_, value)) = line.split_once("REG_SZ") {
+                java_homes.push(PathBuf::from(value.trim()));
+            }
+        }
+    }
+
+    java_homes
+}
@@ -1,4 +1,5 @@
 use iced::subscription;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use shared_child::SharedChild;
 use std::{
@@ -14,6 +15,7 @@ use std::{
         Arc,
     },
     thread::{self, JoinHandle},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use uuid::Uuid;
 
@@ -21,12 +23,17 @@ pub enum State {
     Checking(Option<GameSettings>),
     Launching(GameSettings),
     GettingLogs((Receiver<String>, JoinHandle<()>)),
+    Attaching(RunningGame),
+    FollowingLogFile(BufReader<File>, GameHandle),
     Idle,
 }
 #[derive(Debug, Clone)]
 pub enum Progress {
     Checked(Option<Missing>),
     Started(Arc<SharedChild>),
+    StartedDetached(Arc<SharedChild>, String),
+    Attached(RunningGame),
+    Warned(String),
     GotLog(String),
     Finished,
     Errored(String),
@@ -34,9 +41,8 @@ pub enum Progress {
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Missing {
-    Java8,
-    Java17,
-    Java21,
+    Java(u32),
+    JavaRuntime(String),
     VersionFiles(Vec<super::downloader::Download>),
     VanillaJson(String, String),
 }
@@ -45,9 +51,7 @@ pub enum Missing {
 pub enum JavaType {
     System,
     Custom,
-    LauncherJava8,
-    LauncherJava17,
-    LauncherJava21,
+    Launcher(u32),
     Automatic,
 }
 
@@ -61,6 +65,41 @@ pub fn start<I: 'static + Hash + Copy + Send + Sync>(
         move |state| launcher(id, state),
     )
 }
+
+// Used to follow the logs of a detached game that was started by a previous launcher session.
+pub fn attach<I: 'static + Hash + Copy + Send + Sync>(
+    id: I,
+    running_game: RunningGame,
+) -> iced::Subscription<(I, Progress)> {
+    subscription::unfold(id, State::Attaching(running_game), move |state| {
+        launcher(id, state)
+    })
+}
+
+// Detached games are saved to a file so the launcher can attach to them when reopened.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct RunningGame {
+    pub pid: u32,
+    pub log_file: String,
+    pub version: String,
+    // tells the game apart from a process that reused its pid
+    #[serde(default)]
+    pub start_time: String,
+}
+
+pub enum GameHandle {
+    Child(Arc<SharedChild>),
+    Pid(u32),
+}
+
+impl GameHandle {
+    fn is_running(&self) -> bool {
+        match self {
+            GameHandle::Child(child) => matches!(child.try_wait(), Ok(None)),
+            GameHandle::Pid(pid) => is_process_running(*pid),
+        }
+    }
+}
 #[derive(Debug, PartialEq, Clone)]
 pub struct GameSettings {
     pub account: super::auth::MinecraftAccount,
@@ -68,26 +107,136 @@ pub struct GameSettings {
     pub jvm: String,
     pub jvmargs: Vec<String>,
     pub ram: f64,
+    pub auto_ram: bool,
     pub game_directory: String,
     pub java_type: JavaType,
     pub game_wrapper_commands: Vec<String>,
     pub enviroment_variables: HashMap<String, String>,
+    pub detached: bool,
+    pub jvm_preset: String,
+    pub jvm_presets: Vec<super::java::JvmPreset>,
+    pub java_agents: Vec<JavaAgent>,
+    pub system_properties: Vec<SystemProperty>,
+}
+
+// Added as -javaagent:<path>[=<options>]
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct JavaAgent {
+    pub path: String,
+    pub options: String,
+}
+
+// Added as -D<key>=<value>
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct SystemProperty {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct LaunchCommand {
+    pub program: String,
+    pub java: String,
+    pub args: Vec<String>,
+    pub enviroment_variables: HashMap<String, String>,
+    pub working_directory: String,
+}
+
+impl LaunchCommand {
+    pub fn to_command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command
+            .args(&self.args)
+            .envs(&self.enviroment_variables)
+            .current_dir(&self.working_directory);
+        command
+    }
+
+    // Writes a ready-to-run script (.sh on Linux, .bat on Windows) and returns its path.
+    pub fn export_script(&self, name: &str, token: Option<&str>) -> std::io::Result<String> {
+        let scripts_dir = format!("{}/minelander_scripts", get_minecraft_dir());
+        fs::create_dir_all(&scripts_dir)?;
+
+        let windows = std::env::consts::OS == "windows";
+        let program = find_command(&self.program).unwrap_or(self.program.clone());
+
+        // a redacted token is read from an enviroment variable when the script runs
+        let format_arg = |arg: &String| match token {
+            Some(token) if !token.is_empty() && arg == token => match windows {
+                true => String::from("%MINELANDER_ACCESS_TOKEN%"),
+                false => String::from("\"$MINELANDER_ACCESS_TOKEN\""),
+            },
+            _ => quote_script_arg(arg, windows),
+        };
+
+        let mut script = String::new();
+        let script_path = if windows {
+            script.push_str("@echo off\r\n");
+            script.push_str(&format!(
+                "cd /d {}\r\n",
+                quote_script_arg(&self.working_directory, windows)
+            ));
+            for (key, value) in &self.enviroment_variables {
+                script.push_str(&format!("set \"{}={}\"\r\n", key, value.replace('%', "%%")));
+            }
+
+            let mut command_line = vec![quote_script_arg(&program, windows)];
+            command_line.extend(self.args.iter().map(format_arg));
+            script.push_str(&command_line.join(" "));
+            script.push_str("\r\n");
+
+            format!("{}/{}.bat", scripts_dir, name)
+        } else {
+            script.push_str("#!/bin/sh\n");
+            script.push_str(&format!(
+                "cd {} || exit 1\n",
+                quote_script_arg(&self.working_directory, windows)
+            ));
+            for (key, value) in &self.enviroment_variables {
+                script.push_str(&format!(
+                    "export {}={}\n",
+                    key,
+                    quote_script_arg(value, windows)
+                ));
+            }
+
+            let mut command_line = vec![format!("exec {}", quote_script_arg(&program, windows))];
+            command_line.extend(self.args.iter().map(format_arg));
+            script.push_str(&command_line.join(" \\\n    "));
+            script.push('\n');
+
+            format!("{}/{}.sh", scripts_dir, name)
+        };
+
+        File::create(&script_path)?.write_all(script.as_bytes())?;
+
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&script_path, fs::Permissions::from_mode(0o755))?;
+        }
+
+        Ok(script_path)
+    }
+}
+
+fn quote_script_arg(arg: &str, windows: bool) -> String {
+    if windows {
+        format!("\"{}\"", arg.replace('%', "%%").replace('"', "\"\""))
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
 }
+
 async fn launcher<I: Copy>(id: I, state: State) -> ((I, Progress), State) {
     match state {
         State::Checking(game_settings) => {
             let game_settings = game_settings.unwrap();
             let minecraft_dir = get_minecraft_dir();
-            let version_dir = format!("{}/versions/{}", minecraft_dir, game_settings.game_version);
 
             // game json file
-            let jsonpathstring = format!(
-                "{}/versions/{}/{}.json",
-                &minecraft_dir, game_settings.game_version, game_settings.game_version
-            );
-            let jsonpath = Path::new(&jsonpathstring);
-            let mut json_file = match File::open(jsonpath) {
-                Ok(file) => file,
+            let p = match get_version_json(&game_settings.game_version) {
+                Ok(ok) => ok,
                 Err(e) => {
                     return (
                         (
@@ -98,10 +247,6 @@ async fn launcher<I: Copy>(id: I, state: State) -> ((I, Progress), State) {
                     )
                 }
             };
-            let mut json_file_content = String::new();
-            json_file.read_to_string(&mut json_file_content).unwrap();
-            let content = serde_json::from_str(&json_file_content);
-            let mut p: Value = content.unwrap();
 
             // check for missing json
 
@@ -151,156 +296,20 @@ async fn launcher<I: Copy>(id: I, state: State) -> ((I, Progress), State) {
                 }
             }
 
-            let is_natives_folder_empty = match fs::read_dir(format!("{}/natives", version_dir)) {
-                Ok(ok) => ok.count() == 0,
-                Err(_) => {
-                    println!("Natives folder not found, ignoring.");
-
-                    false
-                }
-            };
-
-            let mut missing_files_list = Vec::new();
-
-            let modded = !p["inheritsFrom"].is_null();
-            if modded {
-                match super::downloader::get_libraries(
-                    &minecraft_dir,
-                    p["libraries"].as_array().unwrap(),
-                    &version_dir,
-                ) {
-                    Ok(ok) => {
-                        for i in ok {
-                            if !Path::new(&i.path).exists() {
-                                if i.path.contains("natives.jar") {
-                                    if is_natives_folder_empty {
-                                        missing_files_list.push(i);
-                                        continue;
-                                    } else {
-                                        continue;
-                                    }
-                                }
-                                missing_files_list.push(i);
-                            }
-                        }
-                    }
-                    Err(e) => println!("Failed to get libraries, ignoring. -> {e}"),
-                }
-
-                let mut vanilla_json_content = String::new();
-
-                let mut vanilla_json_file = match File::open(format!(
-                    "{}/versions/{}/{}.json",
-                    minecraft_dir,
-                    game_settings.game_version,
-                    p["inheritsFrom"].as_str().unwrap()
-                )) {
+            // check for missing libraries, natives and client jar
+            let (mut missing_files_list, p) =
+                match get_missing_files(&p, &game_settings.game_version) {
                     Ok(ok) => ok,
-                    Err(_) => panic!("no!!!"),
+                    Err(e) => return ((id, Progress::Errored(e)), State::Idle),
                 };
 
-                vanilla_json_file
-                    .read_to_string(&mut vanilla_json_content)
-                    .unwrap();
-
-                let content = serde_json::from_str(&vanilla_json_content);
-                p = content.unwrap();
-            }
-
-            // check for missing libraries, natives, assets and client jar
-
-            let version_jar_path = format!(
-                "{}/versions/{}/{}.jar",
-                minecraft_dir, game_settings.game_version, game_settings.game_version
-            );
-
-            if !Path::new(&version_jar_path).exists()
-                || (Path::new(&version_jar_path).exists()
-                    && super::is_file_empty(&version_jar_path))
-            {
-                missing_files_list.push(super::downloader::Download {
-                    path: version_jar_path,
-                    url: p["downloads"]["client"]["url"]
-                        .as_str()
-                        .unwrap()
-                        .to_string(),
-                })
-            }
-
-            match super::downloader::get_libraries(
-                &minecraft_dir,
-                p["libraries"].as_array().unwrap(),
-                &version_dir,
-            ) {
-                Ok(ok) => {
-                    for i in ok {
-                        if !Path::new(&i.path).exists() {
-                            if i.path.contains("natives.jar") {
-                                if is_natives_folder_empty {
-                                    missing_files_list.push(i);
-                                    continue;
-                                } else {
-                                    continue;
-                                }
-                            }
-                            missing_files_list.push(i);
-                        }
-                    }
-                }
-                Err(e) => println!("Failed to get libraries, ignoring. -> {e}"),
-            }
-
-            //check for asset index and downloads it if doesn't exist
-            let asset_index_path = format!(
-                "{}/assets/indexes/{}.json",
-                minecraft_dir,
-                p["assets"].as_str().unwrap()
-            );
-            if !Path::new(&asset_index_path).exists() {
-                match reqwest::get(p["assetIndex"]["url"].as_str().unwrap()).await {
-                    Ok(ok) => {
-                        let bytes = ok.bytes().await.unwrap();
-
-                        match fs::create_dir_all(format!("{}/assets/indexes", minecraft_dir)) {
-                            Ok(ok) => ok,
-                            Err(e) => {
-                                println!("Failed to create asset index directory, ignoring. -> {e}")
-                            }
-                        }
-                        match File::create(&asset_index_path) {
-                            Ok(mut ok) => match ok.write_all(&bytes) {
-                                Ok(ok) => ok,
-                                Err(e) => {
-                                    println!("Failed to write to asset index, ignoring. -> {e}")
-                                }
-                            },
-                            Err(e) => println!("Failed to create asset index, ignoring. -> {e}"),
-                        }
-                    }
-                    Err(e) => println!("Failed to download asset index, ignoring. -> {e}"),
-                };
+            //check for asset index and downloads it if it is missing or corrupt
+            let client = super::network::client();
+            if let Err(e) = super::downloader::get_asset_index(&client, &minecraft_dir, &p).await {
+                println!("Failed to download asset index, ignoring. -> {e}")
             }
 
-            if Path::new(&format!(
-                "{}/assets/indexes/{}.json",
-                minecraft_dir,
-                p["assets"].as_str().unwrap()
-            ))
-            .exists()
-            {
-                let asset_p = super::getjson(asset_index_path);
-
-                match super::downloader::get_assets(&minecraft_dir, asset_p) {
-                    Ok(ok) => {
-                        for i in ok {
-                            if !Path::new(&i.path).exists() {
-                                missing_files_list.push(i)
-                            }
-                        }
-                    }
-                    Err(e) => println!("Failed to get assets, ignoring. -> {e}"),
-                }
-            }
+            missing_files_list.extend(get_missing_assets(&p));
 
             if !missing_files_list.is_empty() {
                 return (
@@ -312,230 +321,85 @@ async fn launcher<I: Copy>(id: I, state: State) -> ((I, Progress), State) {
                 );
             }
             // check for java
-            match game_settings.java_type {
-                JavaType::LauncherJava8 => {
-                    if !Path::new(&format!("{}/minelander_java/java8", minecraft_dir)).exists() {
-                        return ((id, Progress::Checked(Some(Missing::Java8))), State::Idle);
-                    }
-                }
-                JavaType::LauncherJava17 => {
-                    if !Path::new(&format!("{}/minelander_java/java17", minecraft_dir)).exists() {
-                        return ((id, Progress::Checked(Some(Missing::Java17))), State::Idle);
-                    }
-                }
-                JavaType::LauncherJava21 => {
-                    if !Path::new(&format!("{}/minelander_java/java21", minecraft_dir)).exists() {
-                        return ((id, Progress::Checked(Some(Missing::Java21))), State::Idle);
-                    }
-                }
-                JavaType::Automatic => {
-                    let java_version = if let Some(java) = p["javaVersion"]["majorVersion"].as_i64()
-                    {
-                        java
-                    } else if let Some(java) = p["javaVersion"]["Version"].as_i64() {
-                        java
-                    } else {
-                        17
-                    };
+            if let Some(missing_java) = get_missing_java(&game_settings.java_type, &p) {
+                return ((id, Progress::Checked(Some(missing_java))), State::Idle);
+            }
 
-                    if java_version > 17
-                        && !Path::new(&format!("{}/minelander_java/java21", minecraft_dir)).exists()
-                    {
-                        return ((id, Progress::Checked(Some(Missing::Java21))), State::Idle);
-                    } else if java_version > 8
-                        && !Path::new(&format!("{}/minelander_java/java17", minecraft_dir)).exists()
-                    {
-                        return ((id, Progress::Checked(Some(Missing::Java17))), State::Idle);
-                    } else if java_version == 8
-                        && !Path::new(&format!("{}/minelander_java/java8", minecraft_dir)).exists()
-                    {
-                        return ((id, Progress::Checked(Some(Missing::Java8))), State::Idle);
-                    }
+            // check if the chosen java can run this version
+            let java_path = match game_settings.java_type {
+                JavaType::System => find_command("java"),
+                JavaType::Custom => Some(game_settings.jvm.clone()),
+                JavaType::Launcher(java) => Some(super::java::get_launcher_java_path(java)),
+                _ => None,
+            };
+            let mut warnings = vec![];
+            if let Some(java_path) = java_path {
+                match super::java::probe(&java_path) {
+                    Ok(java) => match check_java_compatibility(&java, &p) {
+                        Ok(Some(warning)) => warnings.push(warning),
+                        Ok(None) => {}
+                        Err(e) => return ((id, Progress::Errored(e)), State::Idle),
+                    },
+                    Err(e) => println!("{e}, ignoring."),
                 }
-
-                _ => {}
             }
-
-            (
-                (id, Progress::Checked(None)),
-                State::Launching(game_settings),
-            )
+            warnings.extend(check_memory(&game_settings));
+
+            match warnings.is_empty() {
+                true => (
+                    (id, Progress::Checked(None)),
+                    State::Launching(game_settings),
+                ),
+                false => (
+                    (id, Progress::Warned(warnings.join(" "))),
+                    State::Launching(game_settings),
+                ),
+            }
         }
         State::Launching(game_settings) => {
-            let minecraft_directory = get_minecraft_dir();
-
-            let game_dir = if game_settings.game_directory == *"Default" {
-                env::set_current_dir(&minecraft_directory)
-                    .expect("Failed to open instance folder!");
-                minecraft_directory.clone()
-            } else {
-                let gamedirpath = format!(
-                    "{}/minelander_instances/{}",
-                    minecraft_directory, game_settings.game_directory
-                );
-                fs::create_dir_all(&gamedirpath).unwrap();
-                env::set_current_dir(&gamedirpath).expect("Failed to open instance folder!");
-                gamedirpath
-            };
+            let detached = game_settings.detached;
+            let game_version = game_settings.game_version.clone();
 
-            let assets_dir = format!("{}/assets", &minecraft_directory);
-
-            // json file {
-            let jsonpathstring = format!(
-                "{}/versions/{}/{}.json",
-                &minecraft_directory, game_settings.game_version, game_settings.game_version
-            );
-            let jsonpath = Path::new(&jsonpathstring);
-            let mut json_file = match File::open(jsonpath) {
+            let launch_command = match build_launch_command(game_settings) {
                 Ok(ok) => ok,
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
-            };
-            let mut json_file_content = String::new();
-            json_file.read_to_string(&mut json_file_content).unwrap();
-            let content = serde_json::from_str(&json_file_content);
-            let p: Value = content.unwrap();
-            // } json file
-
-            let main_class = &p["mainClass"].as_str().unwrap();
-            let asset_index = p["assets"].as_str().unwrap_or("").to_string();
-            let native_directory = format!(
-                "{}/versions/{}/natives",
-                &minecraft_directory, game_settings.game_version
-            );
-
-            let mut library_list = lib_manager(&p);
-
-            let mut version_jvm_args = get_game_jvm_args(&p, &native_directory);
-
-            //
-            let mut version_game_args = vec![];
-
-            let uuid = if game_settings.account.uuid.is_empty() {
-                generate_uuid(&game_settings.account.username)
-            } else {
-                game_settings.account.uuid
-            };
-
-            // this is used to get game args.
-            let gamedata = vec![
-                game_settings.account.username,
-                game_settings.game_version.clone(),
-                game_dir.to_string(),
-                assets_dir,
-                asset_index,
-                uuid,
-                game_settings.account.token,
-                String::from("{}"),
-                String::from("legacy"),
-                String::from("Release"),
-                String::from("Modified"),
-                library_list.clone(),
-            ];
-
-            let is_modded = if game_settings.game_version.to_lowercase().contains("fabric")
-                || game_settings.game_version.to_lowercase().contains("forge")
-                || !p["inheritsFrom"].is_null()
-            {
-                let (modded_jvm_args, modded_game_args, vanilla_version_library_list) =
-                    modded(&p, &game_settings.game_version, gamedata.clone());
-                version_jvm_args.extend(modded_jvm_args);
-                library_list.push_str(&vanilla_version_library_list);
-
-                version_game_args = modded_game_args;
-                true
-            } else {
-                false
-            };
-
-            let (java_path, java_args) = match game_settings.java_type{
-                JavaType::System => ("java".to_owned(), get_vec_from("-XX:+UnlockExperimentalVMOptions -XX:+UnlockDiagnosticVMOptions -XX:+AlwaysActAsServerClassMachine -XX:+AlwaysPreTouch -XX:+DisableExplicitGC -XX:+UseNUMA -XX:NmethodSweepActivity=1 -XX:ReservedCodeCacheSize=400M -XX:NonNMethodCodeHeapSize=12M -XX:ProfiledCodeHeapSize=194M -XX:NonProfiledCodeHeapSize=194M -XX:-DontCompileHugeMethods -XX:MaxNodeLimit=240000 -XX:NodeLimitFudgeFactor=8000 -XX:+UseVectorCmov -XX:+PerfDisableSharedMem -XX:+UseFastUnorderedTimeStamps -XX:+UseCriticalJavaThreadPriority -XX:ThreadPriorityPolicy=1 -XX:AllocatePrefetchStyle=3")),
-                JavaType::Custom => (game_settings.jvm, game_settings.jvmargs),
-                JavaType::LauncherJava8 => {
-                    let args = get_vec_from("-XX:+UnlockExperimentalVMOptions -XX:+UnlockDiagnosticVMOptions -XX:+AlwaysActAsServerClassMachine -XX:+ParallelRefProcEnabled -XX:+DisableExplicitGC -XX:+AlwaysPreTouch -XX:+AggressiveOpts -XX:MaxInlineLevel=15 -XX:MaxVectorSize=32 -XX:ThreadPriorityPolicy=1 -XX:+UseNUMA -XX:+UseDynamicNumberOfGCThreads -XX:NmethodSweepActivity=1 -XX:ReservedCodeCacheSize=350M -XX:-DontCompileHugeMethods -XX:MaxNodeLimit=240000 -XX:NodeLimitFudgeFactor=8000 -Dgraal.CompilerConfiguration=community");
-
-                    if std::env::consts::OS == "windows"{
-                        (format!("{}/minelander_java/java8/bin/javaw.exe", minecraft_directory), args) 
-                    } else {
-                        (format!("{}/minelander_java/java8/bin/java", minecraft_directory), args)
-                    }
-                },
-                JavaType::LauncherJava17 => {
-                    let args = get_vec_from("-XX:+UnlockExperimentalVMOptions -XX:+UnlockDiagnosticVMOptions -XX:+AlwaysActAsServerClassMachine -XX:+AlwaysPreTouch -XX:+DisableExplicitGC -XX:+UseNUMA -XX:NmethodSweepActivity=1 -XX:ReservedCodeCacheSize=400M -XX:NonNMethodCodeHeapSize=12M -XX:ProfiledCodeHeapSize=194M -XX:NonProfiledCodeHeapSize=194M -XX:-DontCompileHugeMethods -XX:MaxNodeLimit=240000 -XX:NodeLimitFudgeFactor=8000 -XX:+UseVectorCmov -XX:+PerfDisableSharedMem -XX:+UseFastUnorderedTimeStamps -XX:+UseCriticalJavaThreadPriority -XX:ThreadPriorityPolicy=1 -XX:AllocatePrefetchStyle=3");
-
-                    if std::env::consts::OS == "windows"{
-                        (format!("{}/minelander_java/java17/bin/javaw.exe", minecraft_directory), args)    
-                    } else {
-                        (format!("{}/minelander_java/java17/bin/java", minecraft_directory), args)
-                    }
-                },
-                JavaType::LauncherJava21 => {
-                    let args = get_vec_from("-XX:+UnlockExperimentalVMOptions -XX:+UnlockDiagnosticVMOptions -XX:+AlwaysActAsServerClassMachine -XX:+AlwaysPreTouch -XX:+DisableExplicitGC -XX:+UseNUMA -XX:NmethodSweepActivity=1 -XX:ReservedCodeCacheSize=400M -XX:NonNMethodCodeHeapSize=12M -XX:ProfiledCodeHeapSize=194M -XX:NonProfiledCodeHeapSize=194M -XX:-DontCompileHugeMethods -XX:MaxNodeLimit=240000 -XX:NodeLimitFudgeFactor=8000 -XX:+UseVectorCmov -XX:+PerfDisableSharedMem -XX:+UseFastUnorderedTimeStamps -XX:+UseCriticalJavaThreadPriority -XX:ThreadPriorityPolicy=1 -XX:AllocatePrefetchStyle=3");
-
-                    if std::env::consts::OS == "windows"{
-                        (format!("{}/minelander_java/java21/bin/javaw.exe", minecraft_directory), args)    
-                    } else {
-                        (format!("{}/minelander_java/java21/bin/java", minecraft_directory), args)
-                    }
-                }
-                JavaType::Automatic => automatic_java(p.clone(), &game_settings.game_version, is_modded),
+                Err(e) => return ((id, Progress::Errored(e)), State::Idle),
             };
-
-            library_list.push_str(&format!(
-                "{}/versions/{}/{}.jar",
-                &minecraft_directory, game_settings.game_version, game_settings.game_version
-            ));
-
-            if let Some(arguments) = p["arguments"]["game"].as_array() {
-                let mut str_arguments = vec![];
-                for i in arguments {
-                    if i.is_string() {
-                        str_arguments.push(i.as_str().unwrap_or("").to_owned())
-                    } else if i["value"].is_string() {
-                        str_arguments.push(i["value"].as_str().unwrap().to_owned())
-                    }
-                }
-
-                version_game_args.extend_from_slice(&get_game_args(str_arguments, &gamedata));
-            } else if let Some(arguments) = p["minecraftArguments"].as_str() {
-                let oldargs: Vec<String> = arguments
-                    .to_string()
-                    .split_whitespace()
-                    .map(String::from)
-                    .collect();
-
-                version_game_args.extend_from_slice(&get_game_args(oldargs, &gamedata))
+            if let Err(e) = fs::create_dir_all(&launch_command.working_directory) {
+                return (
+                    (
+                        id,
+                        Progress::Errored(format!("Failed to create instance folder: {e}")),
+                    ),
+                    State::Idle,
+                );
             }
 
-            let mut wrapper_commands = game_settings.game_wrapper_commands;
-            let has_wrapper_commands;
-
-            let mut game_command = if !wrapper_commands.is_empty() {
-                has_wrapper_commands = true;
-                Command::new(wrapper_commands.remove(0))
-            } else {
-                has_wrapper_commands = false;
-                Command::new(&java_path)
-            };
-
-            if has_wrapper_commands {
-                game_command.args(wrapper_commands).arg(&java_path);
-            }
+            env::set_current_dir(&launch_command.working_directory)
+                .expect("Failed to open instance folder!");
 
-            game_command
-                .arg(format!("-Xmx{}M", game_settings.ram * 1024.))
-                .args(java_args.clone())
-                .args(version_jvm_args.clone())
-                .arg("-cp")
-                .arg(library_list.clone())
-                .arg(main_class)
-                .args(version_game_args.clone());
-            game_command.envs(game_settings.enviroment_variables);
+            let game_command = launch_command.to_command();
 
             if cfg!(debug_assertions) {
                 println!("{:?}", game_command)
             }
 
-            if command_exists(game_command.get_program().to_str().unwrap()) {
+            if command_exists(&launch_command.program) {
+                if detached {
+                    return match run_detached_game(game_command, &game_version) {
+                        Ok((child, log_file, reader)) => (
+                            (id, Progress::StartedDetached(child.clone(), log_file)),
+                            State::FollowingLogFile(reader, GameHandle::Child(child)),
+                        ),
+                        Err(e) => (
+                            (
+                                id,
+                                Progress::Errored(format!("Failed to start game process: {e}")),
+                            ),
+                            State::Idle,
+                        ),
+                    };
+                }
+
                 let game_process_receiver = run_and_log_game(game_command);
                 if let Ok(game_pr_rec) = game_process_receiver.await {
                     (
@@ -574,6 +438,62 @@ async fn launcher<I: Copy>(id: I, state: State) -> ((I, Progress), State) {
             }
         }
 
+        State::Attaching(running_game) => match File::open(&running_game.log_file) {
+            Ok(file) => (
+                (id, Progress::Attached(running_game.clone())),
+                State::FollowingLogFile(BufReader::new(file), GameHandle::Pid(running_game.pid)),
+            ),
+            Err(e) => {
+                remove_running_game();
+                (
+                    (
+                        id,
+                        Progress::Errored(format!("Failed to open game log file: {e}")),
+                    ),
+                    State::Idle,
+                )
+            }
+        },
+
+        State::FollowingLogFile(mut reader, handle) => {
+            let mut line = Vec::new();
+            loop {
+                match reader.read_until(b'\n', &mut line) {
+                    Ok(0) => {
+                        if !handle.is_running() {
+                            remove_running_game();
+                            if line.is_empty() {
+                                return ((id, Progress::Finished), State::Idle);
+                            }
+                            break;
+                        }
+                        // the game didn't write anything new yet
+                        tokio::time::sleep(Duration::from_millis(200)).await;
+                    }
+                    Ok(_) => {
+                        if line.ends_with(b"\n") {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        return (
+                            (
+                                id,
+                                Progress::Errored(format!("Failed to read game logs: {e}")),
+                            ),
+                            State::Idle,
+                        )
+                    }
+                }
+            }
+
+            let log_line = String::from_utf8_lossy(&line).trim_end().to_owned();
+            (
+                (id, Progress::GotLog(log_line)),
+                State::FollowingLogFile(reader, handle),
+            )
+        }
+
         State::Idle => iced::futures::future::pending().await,
     }
 }
@@ -597,7 +517,10 @@ async fn run_and_log_game(
             for line in reader.lines() {
                 match line {
                     Ok(line) => {
-                        sender.send(line).expect("Failed to send log line");
+                        // the launcher stopped listening, the game keeps running without logs
+                        if sender.send(line).is_err() {
+                            break;
+                        }
                     }
                     Err(err) => eprintln!("Error reading child output: {}", err),
                 }
@@ -609,7 +532,10 @@ async fn run_and_log_game(
             for line in reader.lines() {
                 match line {
                     Ok(line) => {
-                        sender.send(line).expect("Failed to send log line");
+                        // the launcher stopped listening, the game keeps running without logs
+                        if sender.send(line).is_err() {
+                            break;
+                        }
                     }
                     Err(err) => eprintln!("Error reading child output: {}", err),
                 }
@@ -625,6 +551,155 @@ async fn run_and_log_game(
     Ok(((receiver, child_thread), child_arc))
 }
 
+// Starts the game writing its output to a log file, so it keeps running after the launcher exits.
+fn run_detached_game(
+    mut game_command: Command,
+    version: &str,
+) -> std::io::Result<(Arc<SharedChild>, String, BufReader<File>)> {
+    let logs_dir = format!("{}/minelander_logs", get_minecraft_dir());
+    fs::create_dir_all(&logs_dir)?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let log_file_path = format!("{}/{}-{}.log", logs_dir, version, timestamp);
+    let log_file = File::create(&log_file_path)?;
+
+    game_command
+        .stdin(Stdio::null())
+        .stdout(log_file.try_clone()?)
+        .stderr(log_file);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        game_command.process_group(0);
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP
+        game_command.creation_flags(0x00000008 | 0x00000200);
+    }
+
+    let child = Arc::new(SharedChild::spawn(&mut game_command)?);
+
+    let running_game = RunningGame {
+        pid: child.id(),
+        log_file: log_file_path.clone(),
+        version: version.to_owned(),
+        start_time: get_process_start_time(child.id()).unwrap_or_default(),
+    };
+    if let Err(e) = save_running_game(&running_game) {
+        println!("Failed to save running game, ignoring. -> {e}")
+    }
+
+    let reader = BufReader::new(File::open(&log_file_path)?);
+    Ok((child, log_file_path, reader))
+}
+
+fn get_running_game_file_path() -> String {
+    format!("{}/minelander_running_game.json", get_minecraft_dir())
+}
+
+fn save_running_game(running_game: &RunningGame) -> std::io::Result<()> {
+    let serialized = serde_json::to_string_pretty(running_game)?;
+    File::create(get_running_game_file_path())?.write_all(serialized.as_bytes())
+}
+
+fn remove_running_game() {
+    if Path::new(&get_running_game_file_path()).exists() {
+        if let Err(e) = fs::remove_file(get_running_game_file_path()) {
+            println!("Failed to remove running game file: {e}")
+        }
+    }
+}
+
+// Returns the detached game left running by a previous launcher session, if it is still alive.
+pub fn get_running_game() -> Option<RunningGame> {
+    let content = fs::read_to_string(get_running_game_file_path()).ok()?;
+    let running_game: RunningGame = serde_json::from_str(&content).ok()?;
+
+    if is_same_process(running_game.pid, &running_game.start_time)
+        && Path::new(&running_game.log_file).exists()
+    {
+        Some(running_game)
+    } else {
+        remove_running_game();
+        None
+    }
+}
+
+pub fn is_process_running(pid: u32) -> bool {
+    match std::env::consts::OS {
+        "linux" => Path::new(&format!("/proc/{}", pid)).exists(),
+        "windows" => match Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+            .output()
+        {
+            // "java.exe","1234","Console","1","100,000 K"
+            Ok(output) => String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .any(|line| line.split(',').nth(1) == Some(&format!("\"{}\"", pid))),
+            Err(_) => false,
+        },
+        _ => false,
+    }
+}
+
+// Checks the pid still belongs to the process started at start_time, pids are reused after it exits.
+pub fn is_same_process(pid: u32, start_time: &str) -> bool {
+    !start_time.is_empty()
+        && is_process_running(pid)
+        && get_process_start_time(pid).is_some_and(|time| time == start_time)
+}
+
+fn get_process_start_time(pid: u32) -> Option<String> {
+    match std::env::consts::OS {
+        "linux" => {
+            let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+            // the process name can have spaces, starttime is the 20th field after it
+            let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+            fields.get(19).map(|time| time.to_string())
+        }
+        "windows" => {
+            let output = Command::new("powershell")
+                .args([
+                    "-NoProfile",
+                    "-Command",
+                    &format!("(Get-Process -Id {}).StartTime.ToFileTimeUtc()", pid),
+                ])
+                .output()
+                .ok()?;
+            let time = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+            match output.status.success() && !time.is_empty() {
+                true => Some(time),
+                false => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+pub fn kill_process(pid: u32) -> std::io::Result<()> {
+    let status = match std::env::consts::OS {
+        "windows" => Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/F"])
+            .status()?,
+        _ => Command::new("kill").arg(pid.to_string()).status()?,
+    };
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!(
+            "failed to stop process {pid}"
+        )))
+    }
+}
+
 // Utility functions {
 pub fn get_minecraft_dir() -> String {
     match std::env::consts::OS {
@@ -659,6 +734,34 @@ pub async fn getinstalledversions() -> Vec<String> {
     versions
 }
 
+// Removes the version folder and its forge installer, the libraries and assets are left for the clean up.
+pub fn uninstall_version(version: &str) -> std::io::Result<()> {
+    let minecraft_dir = get_minecraft_dir();
+    fs::remove_dir_all(format!("{}/versions/{}", minecraft_dir, version))?;
+
+    let forge_folder = format!("{}/minelander_forge/{}", minecraft_dir, version);
+    if Path::new(&forge_folder).exists() {
+        fs::remove_dir_all(&forge_folder)?;
+    }
+    let forge_installer = format!("{}-installer.jar", forge_folder);
+    if Path::new(&forge_installer).exists() {
+        fs::remove_file(forge_installer)?;
+    }
+
+    Ok(())
+}
+
+// Installed versions that inherit from the version, they stop working without it.
+pub fn get_dependent_versions(versions: &[String], version: &str) -> Vec<String> {
+    versions
+        .iter()
+        .filter(|other| {
+            get_version_json(other).is_ok_and(|p| p["inheritsFrom"].as_str() == Some(version))
+        })
+        .cloned()
+        .collect()
+}
+
 fn get_version_order(a: &str, b: &str) -> std::cmp::Ordering {
     let split_a: Vec<&str> = a.split(|c| c == '.' || c == '-').collect();
     let split_b: Vec<&str> = b.split(|c| c == '.' || c == '-').collect();
@@ -724,18 +827,695 @@ fn get_version_order(a: &str, b: &str) -> std::cmp::Ordering {
 }
 // } Utility functions
 
-// Launch functions {
-fn get_game_args(arguments: Vec<String>, gamedata: &[String]) -> Vec<String> {
-    let mut version_game_args = vec![];
-    for i in arguments {
-        match i.as_str() {
-            "${auth_player_name}" => version_game_args.push(gamedata[0].clone()),
-            "${version_name}" => version_game_args.push(gamedata[1].clone()),
-            "${game_directory}" => version_game_args.push(gamedata[2].clone()),
-            "${assets_root}" => version_game_args.push(gamedata[3].clone()),
-            "${assets_index_name}" => version_game_args.push(gamedata[4].clone()),
-            "${auth_uuid}" => version_game_args.push(gamedata[5].clone()),
-            "${auth_session}" => version_game_args.push(gamedata[5].clone()),
+// Dry run {
+#[derive(Debug, Clone, Default)]
+pub struct DryRunReport {
+    pub errors: Vec<String>,
+    pub missing_files: Vec<String>,
+    pub missing_java: Option<Missing>,
+    pub java: Option<super::java::JavaInfo>,
+    pub warnings: Vec<String>,
+    pub unresolved_placeholders: Vec<String>,
+    pub missing_classpath_entries: Vec<String>,
+    pub command_line: Vec<String>,
+}
+
+impl DryRunReport {
+    pub fn is_healthy(&self) -> bool {
+        self.errors.is_empty()
+            && self.missing_files.is_empty()
+            && self.missing_java.is_none()
+            && self.unresolved_placeholders.is_empty()
+            && self.missing_classpath_entries.is_empty()
+    }
+}
+
+impl std::fmt::Display for DryRunReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sections = [
+            ("Errors", &self.errors),
+            ("Warnings", &self.warnings),
+            ("Missing files", &self.missing_files),
+            ("Unresolved placeholders", &self.unresolved_placeholders),
+            ("Missing classpath entries", &self.missing_classpath_entries),
+        ];
+
+        for (title, entries) in sections {
+            writeln!(f, "{} ({}):", title, entries.len())?;
+            for entry in entries {
+                writeln!(f, "  {}", entry)?;
+            }
+        }
+
+        if let Some(missing_java) = &self.missing_java {
+            writeln!(f, "Missing Java: {:?}", missing_java)?;
+        }
+
+        match &self.java {
+            Some(java) => writeln!(
+                f,
+                "Java: {} ({} {}, {})",
+                java.path, java.vendor, java.version, java.arch
+            )?,
+            None => writeln!(f, "Java: not detected")?,
+        }
+
+        writeln!(f, "Command line:")?;
+        writeln!(f, "  {}", self.command_line.join(" "))?;
+
+        match self.is_healthy() {
+            true => writeln!(f, "Result: OK"),
+            false => writeln!(f, "Result: problems found"),
+        }
+    }
+}
+
+// Runs the launch checks and builds the game command without downloading anything or starting the game.
+pub fn dry_run(game_settings: GameSettings) -> DryRunReport {
+    let mut report = DryRunReport::default();
+    let minecraft_dir = get_minecraft_dir();
+
+    let p = match get_version_json(&game_settings.game_version) {
+        Ok(ok) => ok,
+        Err(e) => {
+            report
+                .errors
+                .push(format!("Failed to read version json: {e}"));
+            return report;
+        }
+    };
+
+    if let Some(vanilla_ver) = p["inheritsFrom"].as_str() {
+        let json_path = format!(
+            "{}/versions/{}/{}.json",
+            minecraft_dir, game_settings.game_version, vanilla_ver
+        );
+
+        if !Path::new(&json_path).exists() {
+            report.missing_files.push(json_path);
+            return report;
+        }
+    }
+
+    let p = match get_missing_files(&p, &game_settings.game_version) {
+        Ok((missing_files, vanilla_json)) => {
+            report
+                .missing_files
+                .extend(missing_files.into_iter().map(|i| i.path));
+            vanilla_json
+        }
+        Err(e) => {
+            report.errors.push(e);
+            return report;
+        }
+    };
+
+    let asset_index_path = format!(
+        "{}/assets/indexes/{}.json",
+        minecraft_dir,
+        p["assets"].as_str().unwrap_or_default()
+    );
+    if Path::new(&asset_index_path).exists() {
+        report
+            .missing_files
+            .extend(get_missing_assets(&p).into_iter().map(|i| i.path));
+    } else {
+        report.missing_files.push(asset_index_path);
+    }
+
+    report.missing_java = get_missing_java(&game_settings.java_type, &p);
+    report.warnings.extend(check_memory(&game_settings));
+
+    let launch_command = match build_launch_command(game_settings) {
+        Ok(ok) => ok,
+        Err(e) => {
+            report
+                .errors
+                .push(format!("Failed to build launch command: {e}"));
+            return report;
+        }
+    };
+
+    let java_path = find_command(&launch_command.java).unwrap_or(launch_command.java.clone());
+    match super::java::probe(&java_path) {
+        Ok(java) => {
+            match check_java_compatibility(&java, &p) {
+                Ok(Some(warning)) => report.warnings.push(warning),
+                Ok(None) => {}
+                Err(e) => report.errors.push(e),
+            }
+            report.java = Some(java);
+        }
+        Err(e) => report.errors.push(e),
+    }
+
+    for arg in &launch_command.args {
+        if arg.contains("${") {
+            report.unresolved_placeholders.push(arg.clone());
+        }
+    }
+
+    let separator = get_classpath_separator();
+    if let Some(index) = launch_command.args.iter().position(|arg| arg == "-cp") {
+        if let Some(classpath) = launch_command.args.get(index + 1) {
+            for entry in classpath.split(separator) {
+                if !entry.is_empty() && !Path::new(entry).exists() {
+                    report.missing_classpath_entries.push(entry.to_owned());
+                }
+            }
+        }
+    }
+
+    report.command_line = vec![launch_command.program];
+    report.command_line.extend(launch_command.args);
+    report
+}
+// } Dry run
+
+// Launch functions {
+fn get_version_json(game_version: &str) -> Result<Value, String> {
+    let json_path = format!(
+        "{}/versions/{}/{}.json",
+        get_minecraft_dir(),
+        game_version,
+        game_version
+    );
+
+    match fs::read_to_string(json_path) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// Returns missing libraries, natives and client jar, and the vanilla json of the version.
+fn get_missing_files(
+    p: &Value,
+    game_version: &str,
+) -> Result<(Vec<super::downloader::Download>, Value), String> {
+    let minecraft_dir = get_minecraft_dir();
+    let version_dir = format!("{}/versions/{}", minecraft_dir, game_version);
+    let mut p = p.clone();
+
+    let is_natives_folder_empty = match fs::read_dir(format!("{}/natives", version_dir)) {
+        Ok(ok) => ok.count() == 0,
+        Err(_) => {
+            println!("Natives folder not found, ignoring.");
+
+            false
+        }
+    };
+
+    let mut missing_files_list = Vec::new();
+
+    if let Some(vanilla_version) = p["inheritsFrom"].as_str().map(|s| s.to_owned()) {
+        let libraries = match p["libraries"].as_array() {
+            Some(some) => some,
+            None => return Err(format!("{game_version} json has no libraries")),
+        };
+        // the folders are made when the files are downloaded
+        for i in super::downloader::get_library_downloads(&minecraft_dir, libraries, &version_dir) {
+            if !i.is_valid(false) {
+                if super::downloader::is_natives_jar(&i.path) {
+                    if is_natives_folder_empty {
+                        missing_files_list.push(i);
+                        continue;
+                    } else {
+                        continue;
+                    }
+                }
+                missing_files_list.push(i);
+            }
+        }
+
+        let vanilla_json_path = format!(
+            "{}/versions/{}/{}.json",
+            minecraft_dir, game_version, vanilla_version
+        );
+        let vanilla_json_content = match fs::read_to_string(vanilla_json_path) {
+            Ok(ok) => ok,
+            Err(e) => return Err(format!("Failed to read {vanilla_version} json: {e}")),
+        };
+        p = match serde_json::from_str(&vanilla_json_content) {
+            Ok(ok) => ok,
+            Err(e) => return Err(format!("Failed to parse {vanilla_version} json: {e}")),
+        };
+    }
+
+    // check for missing libraries, natives, assets and client jar
+
+    let version_jar_path = format!(
+        "{}/versions/{}/{}.jar",
+        minecraft_dir, game_version, game_version
+    );
+
+    let version_jar_url = match p["downloads"]["client"]["url"].as_str() {
+        Some(some) => some.to_owned(),
+        None => return Err(format!("{game_version} json has no client jar")),
+    };
+    let version_jar = super::downloader::Download {
+        path: version_jar_path,
+        url: version_jar_url,
+        sha1: p["downloads"]["client"]["sha1"]
+            .as_str()
+            .map(|s| s.to_owned()),
+        size: p["downloads"]["client"]["size"].as_u64(),
+    };
+    if !version_jar.is_valid(false) {
+        missing_files_list.push(version_jar)
+    }
+
+    let libraries = match p["libraries"].as_array() {
+        Some(some) => some,
+        None => return Err(format!("{game_version} json has no libraries")),
+    };
+    for i in super::downloader::get_library_downloads(&minecraft_dir, libraries, &version_dir) {
+        if !i.is_valid(false) {
+            if super::downloader::is_natives_jar(&i.path) {
+                if is_natives_folder_empty {
+                    missing_files_list.push(i);
+                    continue;
+                } else {
+                    continue;
+                }
+            }
+            missing_files_list.push(i);
+        }
+    }
+
+    Ok((missing_files_list, p))
+}
+
+fn get_missing_assets(p: &Value) -> Vec<super::downloader::Download> {
+    let minecraft_dir = get_minecraft_dir();
+    let asset_index_path = format!(
+        "{}/assets/indexes/{}.json",
+        minecraft_dir,
+        p["assets"].as_str().unwrap()
+    );
+    let mut missing_files_list = Vec::new();
+
+    if Path::new(&asset_index_path).exists() {
+        let asset_p = super::getjson(asset_index_path);
+
+        // only sizes are checked here, hashing every asset would slow down launching
+        for i in super::downloader::get_asset_downloads(&minecraft_dir, &asset_p) {
+            if !i.is_valid(false) {
+                missing_files_list.push(i)
+            }
+        }
+    }
+
+    missing_files_list
+}
+
+fn get_missing_java(java_type: &JavaType, p: &Value) -> Option<Missing> {
+    let is_installed = |java: u32| Path::new(&super::java::get_launcher_java_dir(java)).exists();
+
+    match java_type {
+        JavaType::Launcher(java) => {
+            if !is_installed(*java) {
+                return Some(Missing::Java(*java));
+            }
+        }
+        JavaType::Automatic => {
+            let runtime = get_automatic_runtime(p);
+
+            match super::java::get_launcher_java_major(&runtime) {
+                Some(java) => {
+                    if !is_installed(java) {
+                        return Some(Missing::Java(java));
+                    }
+                }
+                None => {
+                    if !super::java::is_runtime_installed(&runtime) {
+                        return Some(Missing::JavaRuntime(runtime));
+                    }
+                }
+            }
+        }
+
+        _ => {}
+    }
+
+    None
+}
+
+// Name of the minelander_java folder used by Automatic, e.g. java-runtime-gamma or java17.
+fn get_automatic_runtime(p: &Value) -> String {
+    // Mojang's runtimes are used when they are available for this system
+    if let Some(component) = p["javaVersion"]["component"].as_str() {
+        if super::java::get_runtime_platform().is_some() {
+            return component.to_owned();
+        }
+    }
+
+    let java_version = if let Some(java) = p["javaVersion"]["majorVersion"].as_i64() {
+        java
+    } else if let Some(java) = p["javaVersion"]["Version"].as_i64() {
+        java
+    } else {
+        17
+    };
+
+    if java_version > 17 {
+        String::from("java21")
+    } else if java_version > 8 {
+        String::from("java17")
+    } else {
+        String::from("java8")
+    }
+}
+
+// Same as above, for an installed version. Modded versions use the java of their vanilla version.
+pub fn get_version_automatic_runtime(game_version: &str) -> Option<String> {
+    Some(get_automatic_runtime(&get_vanilla_json(game_version)?))
+}
+
+// The json with the Java requirements, modded versions use the one of their vanilla version.
+fn get_vanilla_json(game_version: &str) -> Option<Value> {
+    let p = get_version_json(game_version).ok()?;
+
+    match p["inheritsFrom"].as_str() {
+        Some(vanilla_version) => {
+            let json_path = format!(
+                "{}/versions/{}/{}.json",
+                get_minecraft_dir(),
+                game_version,
+                vanilla_version
+            );
+            serde_json::from_str(&fs::read_to_string(json_path).ok()?).ok()
+        }
+        None => Some(p),
+    }
+}
+
+// Blocks launching when the Java is too old for the version and warns about other mismatches.
+fn check_java_compatibility(
+    java: &super::java::JavaInfo,
+    p: &Value,
+) -> Result<Option<String>, String> {
+    let mut warnings = Vec::new();
+
+    if let Some(required_java) = p["javaVersion"]["majorVersion"].as_u64() {
+        let required_java = required_java as u32;
+
+        if java.major_version < required_java {
+            return Err(format!(
+                "This version needs Java {} or newer, but Java {} was selected. {}",
+                required_java,
+                java.major_version,
+                get_compatible_java_suggestion(required_java)
+            ));
+        }
+
+        if required_java == 8 && java.major_version > 8 {
+            warnings.push(format!(
+                "This version was made for Java 8, some mods may not work with Java {}.",
+                java.major_version
+            ));
+        }
+    }
+
+    if !super::java::is_native_arch(&java.arch) {
+        warnings.push(format!(
+            "Java is {} but the system is {}, natives may fail to load.",
+            java.arch,
+            std::env::consts::ARCH
+        ));
+    }
+
+    match warnings.is_empty() {
+        true => Ok(None),
+        false => Ok(Some(warnings.join(" "))),
+    }
+}
+
+// Warns when the manual memory is more than the system has or can currently give.
+fn check_memory(game_settings: &GameSettings) -> Option<String> {
+    if game_settings.auto_ram {
+        return None;
+    }
+
+    let memory = get_system_memory()?;
+    let ram = (game_settings.ram * 1024.) as u64;
+
+    if ram > memory.total {
+        return Some(format!(
+            "{} MiB of memory are allocated but the system only has {} MiB, the game may crash. Lower it or use automatic memory.",
+            ram, memory.total
+        ));
+    }
+
+    match ram > memory.available {
+        true => Some(format!(
+            "{} MiB of memory are allocated but only {} MiB are available, the game may be slow or crash. Close other programs, lower it or use automatic memory.",
+            ram, memory.available
+        )),
+        false => None,
+    }
+}
+
+fn get_compatible_java_suggestion(required_java: u32) -> String {
+    let compatible_java = super::java::scan()
+        .into_iter()
+        .filter(|java| {
+            java.major_version >= required_java && super::java::is_native_arch(&java.arch)
+        })
+        .min_by_key(|java| java.major_version);
+
+    match compatible_java {
+        Some(java) => format!("Try \"{}\".", super::java::get_display_name(&java)),
+        None => String::from("Try \"Automatic\" to use a Java downloaded by Minelander."),
+    }
+}
+
+fn get_agent_and_property_args(
+    java_agents: &[JavaAgent],
+    system_properties: &[SystemProperty],
+) -> Result<Vec<String>, String> {
+    let mut args = vec![];
+
+    for agent in java_agents {
+        check_java_agent(&agent.path)?;
+
+        match agent.options.is_empty() {
+            true => args.push(format!("-javaagent:{}", agent.path)),
+            false => args.push(format!("-javaagent:{}={}", agent.path, agent.options)),
+        }
+    }
+
+    for property in system_properties {
+        check_system_property_key(&property.key)?;
+        args.push(format!("-D{}={}", property.key, property.value));
+    }
+
+    Ok(args)
+}
+
+pub fn check_java_agent(path: &str) -> Result<(), String> {
+    // the game runs in the instance folder, relative paths would point somewhere else
+    if !Path::new(path).is_absolute() {
+        return Err(format!("Java agent {path} must be an absolute path."));
+    }
+    if !Path::new(path).is_file() {
+        return Err(format!("Java agent {path} doesn't exist."));
+    }
+    if !path.to_lowercase().ends_with(".jar") {
+        return Err(format!("Java agent {path} is not a jar file."));
+    }
+    Ok(())
+}
+
+pub fn check_system_property_key(key: &str) -> Result<(), String> {
+    if key.is_empty() || key.contains(char::is_whitespace) || key.contains('=') {
+        return Err(format!("Invalid system property name: \"{key}\"."));
+    }
+    Ok(())
+}
+
+pub fn get_instance_dir(instance: &str) -> String {
+    match instance {
+        "Default" => get_minecraft_dir(),
+        instance => format!("{}/minelander_instances/{}", get_minecraft_dir(), instance),
+    }
+}
+
+// Builds the full game command. Also used to export it as a script.
+pub fn build_launch_command(game_settings: GameSettings) -> Result<LaunchCommand, String> {
+    let minecraft_directory = get_minecraft_dir();
+
+    let game_dir = get_instance_dir(&game_settings.game_directory);
+
+    let assets_dir = format!("{}/assets", &minecraft_directory);
+
+    // json file {
+    let jsonpathstring = format!(
+        "{}/versions/{}/{}.json",
+        &minecraft_directory, game_settings.game_version, game_settings.game_version
+    );
+    let version = &game_settings.game_version;
+    let json_file_content = match fs::read_to_string(&jsonpathstring) {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("Failed to read {version} json: {e}")),
+    };
+    let p: Value = match serde_json::from_str(&json_file_content) {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("Failed to parse {version} json: {e}")),
+    };
+    // } json file
+
+    let main_class = match p["mainClass"].as_str() {
+        Some(some) => some,
+        None => return Err(format!("{version} json has no main class")),
+    };
+    let asset_index = p["assets"].as_str().unwrap_or("").to_string();
+    let native_directory = format!(
+        "{}/versions/{}/natives",
+        &minecraft_directory, game_settings.game_version
+    );
+
+    let mut libraries = lib_manager(&p);
+    let mut library_list = get_classpath(&libraries);
+
+    let mut version_jvm_args = get_game_jvm_args(&p, &native_directory);
+
+    //
+    let mut version_game_args = vec![];
+
+    let uuid = if game_settings.account.uuid.is_empty() {
+        generate_uuid(&game_settings.account.username)
+    } else {
+        game_settings.account.uuid
+    };
+
+    // this is used to get game args.
+    let gamedata = vec![
+        game_settings.account.username,
+        game_settings.game_version.clone(),
+        game_dir.to_string(),
+        assets_dir,
+        asset_index,
+        uuid,
+        game_settings.account.token,
+        String::from("{}"),
+        String::from("legacy"),
+        String::from("Release"),
+        String::from("Modified"),
+        library_list.clone(),
+    ];
+
+    if !p["inheritsFrom"].is_null() {
+        let (modded_jvm_args, modded_game_args, vanilla_version_library_list) =
+            modded(&p, &game_settings.game_version, gamedata.clone())?;
+        version_jvm_args.extend(modded_jvm_args);
+
+        // loader libraries come first, so their versions win over the vanilla ones
+        libraries.extend(vanilla_version_library_list);
+        library_list = get_classpath(&dedup_libraries(libraries));
+
+        version_game_args = modded_game_args;
+    }
+
+    let vanilla_json = get_vanilla_json(&game_settings.game_version).unwrap_or(p.clone());
+
+    let (java_path, mut java_args) = match game_settings.java_type {
+        JavaType::System => ("java".to_owned(), vec![]),
+        JavaType::Custom => (game_settings.jvm, game_settings.jvmargs),
+        JavaType::Launcher(java) => (super::java::get_launcher_java_path(java), vec![]),
+        JavaType::Automatic => (automatic_java(&vanilla_json), vec![]),
+    };
+
+    // custom JVMs keep their own flags unless a preset is chosen
+    if !(game_settings.java_type == JavaType::Custom && game_settings.jvm_preset == "Automatic") {
+        let java = get_target_java(&game_settings.java_type, &java_path, &vanilla_json);
+
+        if let Some(preset) =
+            get_jvm_preset(&game_settings.jvm_presets, &game_settings.jvm_preset, java)?
+        {
+            java_args.extend(preset.args.split_whitespace().map(|s| s.to_owned()));
+        }
+    }
+
+    if !library_list.is_empty() {
+        library_list.push(get_classpath_separator());
+    }
+    library_list.push_str(&format!(
+        "{}/versions/{}/{}.jar",
+        &minecraft_directory, game_settings.game_version, game_settings.game_version
+    ));
+
+    if let Some(arguments) = p["arguments"]["game"].as_array() {
+        let mut str_arguments = vec![];
+        for i in arguments {
+            if i.is_string() {
+                str_arguments.push(i.as_str().unwrap_or("").to_owned())
+            } else if i["value"].is_string() {
+                str_arguments.push(i["value"].as_str().unwrap().to_owned())
+            }
+        }
+
+        version_game_args.extend_from_slice(&get_game_args(str_arguments, &gamedata));
+    } else if let Some(arguments) = p["minecraftArguments"].as_str() {
+        let oldargs: Vec<String> = arguments
+            .to_string()
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+
+        version_game_args.extend_from_slice(&get_game_args(oldargs, &gamedata))
+    }
+
+    let mut wrapper_commands = game_settings.game_wrapper_commands;
+    let mut args = vec![];
+
+    let program = if !wrapper_commands.is_empty() {
+        let program = wrapper_commands.remove(0);
+        args.extend(wrapper_commands);
+        args.push(java_path.clone());
+        program
+    } else {
+        java_path.clone()
+    };
+
+    match (game_settings.auto_ram, get_system_memory()) {
+        (true, Some(memory)) => {
+            let (max_memory, initial_memory) = get_automatic_memory(&memory, count_mods(&game_dir));
+            args.push(format!("-Xmx{}M", max_memory));
+            args.push(format!("-Xms{}M", initial_memory));
+        }
+        _ => args.push(format!("-Xmx{}M", game_settings.ram * 1024.)),
+    }
+    args.extend(java_args);
+    args.extend(get_agent_and_property_args(
+        &game_settings.java_agents,
+        &game_settings.system_properties,
+    )?);
+    args.extend(version_jvm_args);
+    args.push("-cp".to_owned());
+    args.push(library_list);
+    args.push(main_class.to_string());
+    args.extend(version_game_args);
+
+    Ok(LaunchCommand {
+        program,
+        java: java_path,
+        args,
+        enviroment_variables: game_settings.enviroment_variables,
+        working_directory: game_dir,
+    })
+}
+
+fn get_game_args(arguments: Vec<String>, gamedata: &[String]) -> Vec<String> {
+    let mut version_game_args = vec![];
+    for i in arguments {
+        match i.as_str() {
+            "${auth_player_name}" => version_game_args.push(gamedata[0].clone()),
+            "${version_name}" => version_game_args.push(gamedata[1].clone()),
+            "${game_directory}" => version_game_args.push(gamedata[2].clone()),
+            "${assets_root}" => version_game_args.push(gamedata[3].clone()),
+            "${assets_index_name}" => version_game_args.push(gamedata[4].clone()),
+            "${auth_uuid}" => version_game_args.push(gamedata[5].clone()),
+            "${auth_session}" => version_game_args.push(gamedata[5].clone()),
             "${clientid}" => version_game_args.push(gamedata[5].clone()),
             "${auth_xuid}" => version_game_args.push(gamedata[5].clone()),
             "${auth_access_token}" => version_game_args.push(gamedata[6].clone()),
@@ -796,73 +1576,72 @@ fn get_game_jvm_args(p: &Value, nativedir: &str) -> Vec<String> {
     version_jvm_args
 }
 
-fn automatic_java(mut p: Value, game_version: &String, ismodded: bool) -> (String, Vec<String>) {
-    let mc_dir = get_minecraft_dir();
-
-    let (autojava21path, autojava17path, autojava8path) = if std::env::consts::OS == "windows" {
-        (
-            format!("{}/minelander_java/java21/bin/javaw.exe", mc_dir),
-            format!("{}/minelander_java/java17/bin/javaw.exe", mc_dir),
-            format!("{}/minelander_java/java8/bin/javaw.exe", mc_dir),
-        )
-    } else {
-        (
-            format!("{}/minelander_java/java21/bin/java", mc_dir),
-            format!("{}/minelander_java/java17/bin/java", mc_dir),
-            format!("{}/minelander_java/java8/bin/java", mc_dir),
-        )
-    };
+fn automatic_java(p: &Value) -> String {
+    let runtime = get_automatic_runtime(p);
 
-    if ismodded {
-        let vanillaversion = p["inheritsFrom"].as_str().unwrap_or(game_version.as_str());
-        let vanillajsonpathstring = format!(
-            "{}/versions/{}/{}.json",
-            &mc_dir, game_version, vanillaversion
-        );
+    match super::java::get_launcher_java_major(&runtime) {
+        Some(java) => super::java::get_launcher_java_path(java),
+        None => super::java::get_runtime_java_path(&runtime),
+    }
+}
 
-        let mut vanillajson = File::open(vanillajsonpathstring).unwrap();
+// Major version of the Java that will run the game, None if it couldn't be found.
+fn get_target_java(java_type: &JavaType, java_path: &str, p: &Value) -> Option<u32> {
+    match java_type {
+        JavaType::Launcher(java) => Some(*java),
+        JavaType::Automatic => {
+            let runtime = get_automatic_runtime(p);
+            let required = p["javaVersion"]["majorVersion"].as_u64();
+            super::java::get_launcher_java_major(&runtime).or(required.map(|java| java as u32))
+        }
+        JavaType::System | JavaType::Custom => {
+            let java_path = find_command(java_path).unwrap_or(java_path.to_owned());
+            super::java::probe(&java_path)
+                .ok()
+                .map(|java| java.major_version)
+        }
+    }
+}
 
-        let mut vjsoncontent = String::new();
-        vanillajson.read_to_string(&mut vjsoncontent).unwrap();
-        p = serde_json::from_str(&vjsoncontent).unwrap();
+// "Automatic" uses the first preset that supports the Java version, other presets are checked against it.
+fn get_jvm_preset<'a>(
+    presets: &'a [super::java::JvmPreset],
+    name: &str,
+    java: Option<u32>,
+) -> Result<Option<&'a super::java::JvmPreset>, String> {
+    if name == "Automatic" {
+        return Ok(presets
+            .iter()
+            .find(|preset| java.is_none_or(|java| preset.supports(java))));
     }
-    let requiredjavaversion = p["javaVersion"]["majorVersion"].as_i64().unwrap_or(0);
 
-    let java21args = "-XX:+UnlockExperimentalVMOptions -XX:+UnlockDiagnosticVMOptions -XX:+AlwaysActAsServerClassMachine -XX:+AlwaysPreTouch -XX:+DisableExplicitGC -XX:+UseNUMA -XX:NmethodSweepActivity=1 -XX:ReservedCodeCacheSize=400M -XX:NonNMethodCodeHeapSize=12M -XX:ProfiledCodeHeapSize=194M -XX:NonProfiledCodeHeapSize=194M -XX:-DontCompileHugeMethods -XX:MaxNodeLimit=240000 -XX:NodeLimitFudgeFactor=8000 -XX:+UseVectorCmov -XX:+PerfDisableSharedMem -XX:+UseFastUnorderedTimeStamps -XX:+UseCriticalJavaThreadPriority -XX:ThreadPriorityPolicy=1 -XX:AllocatePrefetchStyle=3";
-    let java17args = "-XX:+UnlockExperimentalVMOptions -XX:+UnlockDiagnosticVMOptions -XX:+AlwaysActAsServerClassMachine -XX:+AlwaysPreTouch -XX:+DisableExplicitGC -XX:+UseNUMA -XX:NmethodSweepActivity=1 -XX:ReservedCodeCacheSize=400M -XX:NonNMethodCodeHeapSize=12M -XX:ProfiledCodeHeapSize=194M -XX:NonProfiledCodeHeapSize=194M -XX:-DontCompileHugeMethods -XX:MaxNodeLimit=240000 -XX:NodeLimitFudgeFactor=8000 -XX:+UseVectorCmov -XX:+PerfDisableSharedMem -XX:+UseFastUnorderedTimeStamps -XX:+UseCriticalJavaThreadPriority -XX:ThreadPriorityPolicy=1 -XX:AllocatePrefetchStyle=3";
-    let java8args = "-XX:+UnlockExperimentalVMOptions -XX:+UnlockDiagnosticVMOptions -XX:+AlwaysActAsServerClassMachine -XX:+ParallelRefProcEnabled -XX:+DisableExplicitGC -XX:+AlwaysPreTouch -XX:+AggressiveOpts -XX:MaxInlineLevel=15 -XX:MaxVectorSize=32 -XX:ThreadPriorityPolicy=1 -XX:+UseNUMA -XX:+UseDynamicNumberOfGCThreads -XX:NmethodSweepActivity=1 -XX:ReservedCodeCacheSize=350M -XX:-DontCompileHugeMethods -XX:MaxNodeLimit=240000 -XX:NodeLimitFudgeFactor=8000 -Dgraal.CompilerConfiguration=community";
+    let preset = match presets.iter().find(|preset| preset.name == name) {
+        Some(some) => some,
+        None => return Err(format!("JVM preset \"{name}\" doesn't exist.")),
+    };
 
-    if requiredjavaversion > 17 {
-        (
-            autojava21path,
-            java21args.split(' ').map(|s| s.to_owned()).collect(),
-        )
-    } else if requiredjavaversion > 8 || requiredjavaversion == 0 {
-        (
-            autojava17path,
-            java17args.split(' ').map(|s| s.to_owned()).collect(),
-        )
-    } else {
-        (
-            autojava8path,
-            java8args.split(' ').map(|s| s.to_owned()).collect(),
-        )
+    match java {
+        Some(java) if !preset.supports(java) => Err(format!(
+            "JVM preset \"{}\" doesn't support Java {}, choose another preset.",
+            name, java
+        )),
+        _ => Ok(Some(preset)),
     }
 }
 
-fn lib_manager(p: &Value) -> String {
+struct Library {
+    name: String,
+    path: String,
+}
+
+fn lib_manager(p: &Value) -> Vec<Library> {
     let os = std::env::consts::OS;
 
     let mc_dir = get_minecraft_dir();
-    let mut library_list = String::new();
+    let mut library_list = Vec::new();
 
     if let Some(libraries) = p["libraries"].as_array() {
         let lib_dir = format!("{}/libraries/", &mc_dir);
-        let separator = match os {
-            "linux" => ':',
-            "windows" => ';',
-            _ => panic!(),
-        };
 
         enum LibraryType {
             Natives,
@@ -901,21 +1680,18 @@ fn lib_manager(p: &Value) -> String {
                             last_piece
                         );
 
-                        library_list.push_str(&libpath);
-                        library_list.push(separator);
+                        library_list.push(Library {
+                            name: libraryname.to_owned(),
+                            path: libpath,
+                        });
                     }
                     LibraryType::Normal => {
-                        let libpath = format!(
-                            "{}{}/{}/{}-{}.jar",
-                            lib_dir,
-                            &firstpiece,
-                            &lpieces.join("/"),
-                            &lpieces[&lpieces.len() - 2],
-                            &lpieces[&lpieces.len() - 1]
-                        );
+                        let libpath = format!("{}{}", lib_dir, get_library_path(libraryname));
 
-                        library_list.push_str(&libpath);
-                        library_list.push(separator);
+                        library_list.push(Library {
+                            name: libraryname.to_owned(),
+                            path: libpath,
+                        });
                     }
                     LibraryType::Old => {
                         if libraryname == "tv.twitch:twitch-platform:6.5" {
@@ -932,8 +1708,10 @@ fn lib_manager(p: &Value) -> String {
                             os
                         );
 
-                        library_list.push_str(&libpath);
-                        library_list.push(separator);
+                        library_list.push(Library {
+                            name: format!("{}:natives-{}", libraryname, os),
+                            path: libpath,
+                        });
                     }
                 }
             }
@@ -942,28 +1720,146 @@ fn lib_manager(p: &Value) -> String {
     library_list
 }
 
+// "group:artifact:version[:classifier][@extension]" -> path inside the libraries folder
+pub fn get_library_path(name: &str) -> String {
+    let (name, extension) = name.split_once('@').unwrap_or((name, "jar"));
+    let pieces: Vec<&str> = name.split(':').collect();
+    let group = pieces[0].replace('.', "/");
+    let artifact = pieces.get(1).unwrap_or(&"");
+    let version = pieces.get(2).unwrap_or(&"");
+
+    match pieces.get(3) {
+        Some(classifier) => format!(
+            "{}/{}/{}/{}-{}-{}.{}",
+            group, artifact, version, artifact, version, classifier, extension
+        ),
+        None => format!(
+            "{}/{}/{}/{}-{}.{}",
+            group, artifact, version, artifact, version, extension
+        ),
+    }
+}
+
+pub fn get_classpath_separator() -> char {
+    match std::env::consts::OS {
+        "linux" => ':',
+        "windows" => ';',
+        _ => panic!(),
+    }
+}
+
+fn get_classpath(libraries: &[Library]) -> String {
+    let separator = get_classpath_separator().to_string();
+
+    libraries
+        .iter()
+        .map(|library| library.path.as_str())
+        .collect::<Vec<_>>()
+        .join(&separator)
+}
+
+// "group:artifact:version[:classifier][@extension]" -> "group:artifact[:classifier]"
+fn get_library_key(name: &str) -> String {
+    let name = name.split('@').next().unwrap_or(name);
+    let pieces: Vec<&str> = name.split(':').collect();
+
+    match pieces.len() {
+        0..=2 => name.to_owned(),
+        3 => format!("{}:{}", pieces[0], pieces[1]),
+        _ => format!("{}:{}:{}", pieces[0], pieces[1], pieces[3..].join(":")),
+    }
+}
+
+// Keeps one library per group:artifact(:classifier), the first one listed wins.
+fn dedup_libraries(libraries: Vec<Library>) -> Vec<Library> {
+    let mut kept_names: HashMap<String, String> = HashMap::new();
+    let mut deduped_libraries = Vec::new();
+
+    for library in libraries {
+        let key = get_library_key(&library.name);
+
+        match kept_names.get(&key) {
+            Some(kept_name) => {
+                if *kept_name != library.name {
+                    println!(
+                        "Dropped {} from classpath, {} is used instead.",
+                        library.name, kept_name
+                    )
+                }
+            }
+            None => {
+                kept_names.insert(key, library.name.clone());
+                deduped_libraries.push(library);
+            }
+        }
+    }
+
+    deduped_libraries
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Loader {
+    Vanilla,
+    Fabric,
+    Quilt,
+    Forge,
+    NeoForge,
+}
+
+// Finds the loader from the libraries of the version json, version names can be anything.
+pub fn get_loader(p: &Value) -> Loader {
+    let library_names: Vec<&str> = match p["libraries"].as_array() {
+        Some(libraries) => libraries
+            .iter()
+            .filter_map(|library| library["name"].as_str())
+            .collect(),
+        None => vec![],
+    };
+    let has_library = |prefix: &str| library_names.iter().any(|name| name.starts_with(prefix));
+
+    // neoforge still ships some net.minecraftforge libraries, so it is checked first
+    if has_library("net.fabricmc:fabric-loader:") {
+        Loader::Fabric
+    } else if has_library("org.quiltmc:quilt-loader:") {
+        Loader::Quilt
+    } else if has_library("net.neoforged") {
+        Loader::NeoForge
+    } else if has_library("net.minecraftforge:forge:")
+        || has_library("net.minecraftforge:fmlloader:")
+    {
+        Loader::Forge
+    } else {
+        Loader::Vanilla
+    }
+}
+
+// jvm args, game args and libraries of the vanilla version
+type VanillaArgs = (Vec<String>, Vec<String>, Vec<Library>);
+
 fn modded(
     p: &Value,
     game_version: &String,
     mut gamedata: Vec<String>,
-) -> (Vec<String>, Vec<String>, String) {
+) -> Result<VanillaArgs, String> {
     let mc_dir = get_minecraft_dir();
 
     let mut modded_game_args = vec![];
 
-    let vanillaversion = p["inheritsFrom"].as_str().unwrap();
+    let vanillaversion = p["inheritsFrom"].as_str().unwrap_or_default();
     let vanillajsonpathstring = format!(
         "{}/versions/{}/{}.json",
         &mc_dir, game_version, vanillaversion
     );
 
-    let mut vanillajson = File::open(vanillajsonpathstring).unwrap();
-
-    let mut vjsoncontent = String::new();
-    vanillajson.read_to_string(&mut vjsoncontent).unwrap();
-    let vjson: Value = serde_json::from_str(&vjsoncontent).unwrap();
-    let new_asset_index = vjson["assets"].as_str().unwrap().to_string();
-    gamedata[4] = new_asset_index;
+    let vjsoncontent = match fs::read_to_string(vanillajsonpathstring) {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("Failed to read {vanillaversion} json: {e}")),
+    };
+    let vjson: Value = match serde_json::from_str(&vjsoncontent) {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("Failed to parse {vanillaversion} json: {e}")),
+    };
+    gamedata[4] = vjson["assets"].as_str().unwrap_or_default().to_string();
 
     if let Some(arguments) = vjson["arguments"]["game"].as_array() {
         let mut base_arguments = Vec::new();
@@ -993,17 +1889,22 @@ fn modded(
         &format!("{}/versions/{}/natives", &mc_dir, game_version),
     );
 
-    let vanilla_library_list = &lib_manager(&vjson);
+    let vanilla_library_list = lib_manager(&vjson);
 
-    (
+    Ok((
         vanilla_version_jvm_args,
         modded_game_args,
-        vanilla_library_list.to_string(),
-    )
+        vanilla_library_list,
+    ))
 }
 // } Launch functions
 
 fn command_exists(command_name: &str) -> bool {
+    find_command(command_name).is_some()
+}
+
+// Resolves a command to its full path using PATH.
+pub fn find_command(command_name: &str) -> Option<String> {
     if let Ok(paths) = env::var("PATH") {
         let path_list: Vec<_> = env::split_paths(&paths).collect();
 
@@ -1012,22 +1913,98 @@ fn command_exists(command_name: &str) -> bool {
 
             if let Ok(metadata) = fs::metadata(&command_path) {
                 if metadata.is_file() {
-                    return true;
+                    return Some(command_path.to_string_lossy().replace('\\', "/"));
                 }
             }
         }
     }
 
-    false
+    None
 }
 
+// Memory in MiB.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SystemMemory {
+    pub total: u64,
+    pub available: u64,
+}
+
+pub fn get_system_memory() -> Option<SystemMemory> {
+    match std::env::consts::OS {
+        "windows" => {
+            let output = Command::new("powershell")
+                .args([
+                    "-NoProfile",
+                    "-Command",
+                    "Get-CimInstance Win32_OperatingSystem | Format-List TotalVisibleMemorySize,FreePhysicalMemory",
+                ])
+                .output()
+                .ok()?;
+            let output = String::from_utf8_lossy(&output.stdout).to_string();
+
+            // lines look like "TotalVisibleMemorySize : 16696212", in KiB
+            let get_value = |key: &str| -> Option<u64> {
+                output
+                    .lines()
+                    .find_map(|line| line.trim().strip_prefix(key)?.trim().strip_prefix(':'))?
+                    .trim()
+                    .parse::<u64>()
+                    .ok()
+            };
+
+            Some(SystemMemory {
+                total: get_value("TotalVisibleMemorySize")? / 1024,
+                available: get_value("FreePhysicalMemory")? / 1024,
+            })
+        }
+        _ => {
+            let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+
+            // lines look like "MemTotal:       16303532 kB"
+            let get_value = |key: &str| -> Option<u64> {
+                meminfo
+                    .lines()
+                    .find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))?
+                    .trim()
+                    .trim_end_matches("kB")
+                    .trim()
+                    .parse::<u64>()
+                    .ok()
+            };
+
+            Some(SystemMemory {
+                total: get_value("MemTotal")? / 1024,
+                available: get_value("MemAvailable")? / 1024,
+            })
+        }
+    }
+}
+
+pub fn count_mods(game_dir: &str) -> usize {
+    match fs::read_dir(format!("{}/mods", game_dir)) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "jar"))
+            .count(),
+        Err(_) => 0,
+    }
+}
+
+// Returns (-Xmx, -Xms) in MiB. Bigger modpacks get more memory, but never more than half of the system or what is available.
+pub fn get_automatic_memory(memory: &SystemMemory, mods: usize) -> (u64, u64) {
+    let wanted = match mods {
+        0 => 2048,
+        1..=50 => 4096,
+        51..=150 => 6144,
+        _ => 8192,
+    };
+
+    let max_memory = wanted.min(memory.total / 2).min(memory.available).max(1024);
+    (max_memory, (max_memory / 2).max(512))
+}
 
 fn generate_uuid(username: &str) -> String {
     let hash = md5::compute(username.as_bytes());
     let uuid = Uuid::from_slice(hash.as_slice()).unwrap();
     uuid.to_string()
 }
-
-fn get_vec_from(str: &str) -> Vec<String> {
-    str.split(' ').map(|s| s.to_owned()).collect()
-}
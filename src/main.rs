@@ -24,14 +24,22 @@ use std::{fs::File, sync::Arc};
 use widget::Renderer;
 
 mod downloader;
+mod forge;
 mod launcher;
+mod network;
 mod theme;
 use theme::Theme;
 mod auth;
+mod java;
 mod screens;
 mod update_manager;
 
 fn main() -> iced::Result {
+    let args: Vec<String> = env::args().collect();
+    if args.iter().any(|arg| arg == "--dry-run") {
+        std::process::exit(dry_run(&args));
+    }
+
     if !Path::new(&get_minecraft_dir()).exists() {
         match fs::create_dir_all(get_minecraft_dir()) {
             Ok(_) => println!("Minecraft directory was created."),
@@ -82,35 +90,80 @@ struct Minelander {
     game_state_text_2: String,
 
     game_ram: f64,
+    auto_ram: bool,
+    system_memory: Option<launcher::SystemMemory>,
+    instance_mod_count: usize,
     current_java_name: String,
     current_java: Java,
     current_game_instance: String,
     game_wrapper_commands: String,
     game_enviroment_variables: String,
-    show_all_versions_in_download_list: bool,
+    version_filter: downloader::VersionFilter,
+    version_search: String,
+    detach_game: bool,
+    jvm_presets: Vec<java::JvmPreset>,
+    current_jvm_preset: String,
+    java_agents: Vec<launcher::JavaAgent>,
+    system_properties: Vec<launcher::SystemProperty>,
 
     all_versions: Vec<String>,
     java_name_list: Vec<String>,
+    detected_java: Vec<java::JavaInfo>,
+    java_detected: bool,
     game_instance_list: Vec<String>,
-    vanilla_versions_download_list: Vec<String>,
-    fabric_versions_download_list: Vec<String>,
+    vanilla_versions_download_list: Vec<downloader::ManifestVersion>,
+    fabric_versions_download_list: Vec<downloader::ManifestVersion>,
     vanilla_version_to_download: String,
     fabric_version_to_download: String,
+    fabric_loader_list: Vec<downloader::LoaderVersion>,
+    fabric_loader_to_download: Option<downloader::LoaderVersion>,
+    quilt_versions_download_list: Vec<downloader::ManifestVersion>,
+    quilt_version_to_download: String,
+    quilt_loader_list: Vec<downloader::LoaderVersion>,
+    quilt_loader_to_download: Option<downloader::LoaderVersion>,
+    forge_loader: forge::ForgeLoader,
+    forge_versions_download_list: Vec<downloader::ManifestVersion>,
+    neoforge_versions_download_list: Vec<downloader::ManifestVersion>,
+    forge_version_to_download: String,
+    forge_list: Vec<downloader::LoaderVersion>,
+    forge_to_download: Option<downloader::LoaderVersion>,
     download_text: String,
     files_download_number: i32,
+    download_concurrency: usize,
+    mirrors: network::Mirrors,
+    network_settings: network::NetworkSettings,
+    network_text: String,
 
     needs_to_update_download_list: bool,
 
     jvm_to_add_name: String,
     jvm_to_add_path: String,
     jvm_to_add_flags: String,
+    java_to_install: String,
+    java_runtimes: Vec<java::LauncherRuntime>,
+    java_runtimes_text: String,
+    versions_text: String,
+    unused_files: Option<downloader::UnusedFiles>,
+    version_to_uninstall: Option<String>,
+    jvm_preset_name: String,
+    jvm_preset_args: String,
+    jvm_preset_min_java: String,
+    jvm_preset_max_java: String,
+    jvm_presets_text: String,
 
     game_instance_to_add: String,
+    java_agent_to_add: launcher::JavaAgent,
+    system_property_to_add: launcher::SystemProperty,
+    java_agents_text: String,
 
     restrict_launch: bool,
-    java_download_size: u8,
+    java_download_size: u16,
 
     game_proccess: GameProcess,
+    game_log_file: String,
+
+    redact_launch_script_token: bool,
+    launch_script_text: String,
 
     update_available: bool,
     last_version: String,
@@ -139,6 +192,7 @@ struct Account {
 #[derive(Default)]
 enum GameProcess {
     Running(Arc<SharedChild>),
+    Detached(u32),
     #[default]
     Null,
 }
@@ -150,7 +204,12 @@ pub enum Screen {
     Settings,
     Installation,
     Java,
+    JavaRuntimes,
+    Versions,
+    JvmPresets,
     GameInstance,
+    JavaAgents,
+    Network,
     Logs,
     ModifyCommand,
     InfoAndUpdates,
@@ -158,7 +217,8 @@ pub enum Screen {
     MicrosoftAccount,
     LocalAccount,
     GettingStarted,
-    GettingStarted2
+    GettingStarted2,
+    ExitPrompt,
 }
 #[derive(Debug, Clone)]
 enum Message {
@@ -172,16 +232,35 @@ enum Message {
     VersionChanged(String),
 
     JavaChanged(String),
+    DetectedJava(Vec<java::JavaInfo>),
     GameInstanceChanged(String),
+    JvmPresetChanged(String),
     GameRamChanged(f64),
+    AutoRamChanged(bool),
     GameWrapperCommandsChanged(String),
     GameEnviromentVariablesChanged(String),
-    ShowAllVersionsInDownloadListChanged(bool),
-
-    GotDownloadList(Result<Vec<Vec<String>>, String>),
-    VanillaVersionToDownloadChanged(String),
-    FabricVersionToDownloadChanged(String),
+    VersionFilterChanged(downloader::VersionFilter),
+    VersionSearchChanged(String),
+    MirrorsChanged(network::Mirrors),
+    MirrorsApplied,
+    NetworkSettingsChanged(network::NetworkSettings),
+    NetworkSettingsApplied,
+    DetachGameChanged(bool),
+
+    GotDownloadList(Result<downloader::DownloadableVersionList, String>),
+    VanillaVersionToDownloadChanged(downloader::ManifestVersion),
+    FabricVersionToDownloadChanged(downloader::ManifestVersion),
+    GotFabricLoaders(Result<Vec<downloader::LoaderVersion>, String>),
+    FabricLoaderToDownloadChanged(downloader::LoaderVersion),
+    QuiltVersionToDownloadChanged(downloader::ManifestVersion),
+    GotQuiltLoaders(Result<Vec<downloader::LoaderVersion>, String>),
+    QuiltLoaderToDownloadChanged(downloader::LoaderVersion),
+    ForgeLoaderChanged(forge::ForgeLoader),
+    ForgeVersionToDownloadChanged(downloader::ManifestVersion),
+    GotForgeVersions(Result<Vec<downloader::LoaderVersion>, String>),
+    ForgeToDownloadChanged(downloader::LoaderVersion),
     InstallVersion(downloader::VersionType),
+    DownloadConcurrencyChanged(u8),
     ManageDownload((usize, downloader::Progress)),
     VanillaJson(Value),
 
@@ -194,9 +273,39 @@ enum Message {
     JvmPathToAddChanged(String),
     JvmFlagsToAddChanged(String),
     JvmAdded,
+    JavaToInstallChanged(String),
+    InstallJava,
+    LoadedJavaRuntimes(Vec<java::LauncherRuntime>),
+    UpdateJavaRuntime(String),
+    CheckedJavaRuntimeUpdate(String, Result<String, String>),
+    ReinstallJavaRuntime(String),
+    UninstallJavaRuntime(String),
+    RepairVersion(String),
+    UninstallVersion(String),
+    UninstallVersionConfirmed,
+    UninstallVersionCancelled,
+    FindUnusedFiles,
+    GotUnusedFiles(Result<downloader::UnusedFiles, String>),
+    DeleteUnusedFiles,
+    DeletedUnusedFiles(Result<u64, String>),
+    JvmPresetToEditChanged(String),
+    JvmPresetNameChanged(String),
+    JvmPresetArgsChanged(String),
+    JvmPresetMinJavaChanged(String),
+    JvmPresetMaxJavaChanged(String),
+    JvmPresetSaved,
+    JvmPresetDeleted,
 
     GameInstanceToAddChanged(String),
     GameInstanceAdded,
+    JavaAgentPathChanged(String),
+    JavaAgentOptionsChanged(String),
+    JavaAgentAdded,
+    JavaAgentRemoved(usize),
+    SystemPropertyKeyChanged(String),
+    SystemPropertyValueChanged(String),
+    SystemPropertyAdded,
+    SystemPropertyRemoved(usize),
 
     CheckedUpdates(Result<(String, String), String>),
     Update,
@@ -214,7 +323,13 @@ enum Message {
     AddedLocalAccount,
     RemoveAccount(String),
 
+    RedactLaunchScriptTokenChanged(bool),
+    ExportLaunchScript,
+    ExportLaunchScriptWithAccount(Option<auth::MinecraftAccount>),
+
     Exit,
+    ExitKeepingGame,
+    ExitClosingGame,
 }
 
 impl Minelander {
@@ -225,6 +340,27 @@ impl Minelander {
             println!("Failed to save user settings!")
         };
 
+        let game_settings = self.get_game_settings();
+        self.launcher.start(game_settings);
+        self.logs.clear();
+        self.game_log_file = String::new();
+        self.current_account_mc_data.token = String::new();
+    }
+
+    // Automatic, System Java, launcher Java and custom JVMs don't need the Java scan.
+    fn is_detected_java_selected(&self) -> bool {
+        self.current_java_name != "Automatic"
+            && self.current_java_name != "System Java"
+            && parse_launcher_java_name(&self.current_java_name).is_none()
+            && !getjson(get_config_file_path())["JVMs"]
+                .as_array()
+                .is_some_and(|jvms| {
+                    jvms.iter()
+                        .any(|jvm| jvm["name"] == self.current_java_name.as_str())
+                })
+    }
+
+    fn get_game_settings(&self) -> launcher::GameSettings {
         let wrapper_commands_vec: Vec<String> = if !self.game_wrapper_commands.is_empty() {
             self.game_wrapper_commands
                 .split(' ')
@@ -253,13 +389,13 @@ impl Minelander {
         let java_type = match self.current_java_name.as_str() {
             "Automatic" => launcher::JavaType::Automatic,
             "System Java" => launcher::JavaType::System,
-            "Java 8 (Minelander)" => launcher::JavaType::LauncherJava8,
-            "Java 17 (Minelander)" => launcher::JavaType::LauncherJava17,
-            "Java 21 (Minelander)" => launcher::JavaType::LauncherJava21,
-            _ => launcher::JavaType::Custom,
+            name => match parse_launcher_java_name(name) {
+                Some(java) => launcher::JavaType::Launcher(java),
+                None => launcher::JavaType::Custom,
+            },
         };
 
-        let game_settings = launcher::GameSettings {
+        launcher::GameSettings {
             account: self.current_account_mc_data.clone(),
             game_version: self.current_version.clone(),
             jvm: self.current_java.path.clone(),
@@ -267,18 +403,150 @@ impl Minelander {
                 .current_java
                 .flags
                 .split(' ')
+                .filter(|s| !s.is_empty())
                 .map(|s| s.to_owned())
                 .collect(),
             ram: self.game_ram,
+            auto_ram: self.auto_ram,
             game_wrapper_commands: wrapper_commands_vec,
             game_directory: self.current_game_instance.clone(),
             java_type,
             enviroment_variables: enviroment_variables_hash_map,
+            detached: self.detach_game,
+            jvm_preset: self.current_jvm_preset.clone(),
+            jvm_presets: self.jvm_presets.clone(),
+            java_agents: self.java_agents.clone(),
+            system_properties: self.system_properties.clone(),
+        }
+    }
+
+    // Java used by the Forge installer, Automatic takes the newest one that can be found.
+    fn get_installer_java(&self) -> String {
+        match self.current_java_name.as_str() {
+            "System Java" => String::from("java"),
+            "Automatic" => match java::get_installed_launcher_java().last() {
+                Some(java) => java::get_launcher_java_path(*java),
+                None => match self
+                    .detected_java
+                    .iter()
+                    .max_by_key(|java| java.major_version)
+                {
+                    Some(java) => java.path.clone(),
+                    None => String::from("java"),
+                },
+            },
+            name => match parse_launcher_java_name(name) {
+                Some(java) => java::get_launcher_java_path(java),
+                None => self.current_java.path.clone(),
+            },
+        }
+    }
+
+    // Saves the Java agents and system properties of the current instance.
+    fn save_java_agents(&mut self) {
+        let result = update_instance_settings(
+            &self.current_game_instance,
+            "java_agents",
+            serde_json::json!(self.java_agents),
+        )
+        .and_then(|_| {
+            update_instance_settings(
+                &self.current_game_instance,
+                "system_properties",
+                serde_json::json!(self.system_properties),
+            )
+        });
+
+        self.java_agents_text = match result {
+            Ok(_) => String::new(),
+            Err(e) => format!("Failed to save: {e}"),
+        };
+    }
+
+    fn load_java_runtimes(&self) -> Command<Message> {
+        let versions = self.all_versions.clone();
+        let java_name = self.current_java_name.clone();
+        let current_version = self.current_version.clone();
+        Command::perform(
+            async move {
+                // the runtime the selected Java launches with
+                let selected_runtime = match java_name.as_str() {
+                    "Automatic" => launcher::get_version_automatic_runtime(&current_version),
+                    name => parse_launcher_java_name(name).map(|java| format!("java{}", java)),
+                };
+                java::get_launcher_runtimes(versions, selected_runtime)
+            },
+            Message::LoadedJavaRuntimes,
+        )
+    }
+
+    // Downloads a runtime again, replacing the installed files.
+    fn install_java_runtime(&mut self, runtime: &str) {
+        if self.restrict_launch {
+            self.java_runtimes_text = String::from("Wait for the current download to finish.");
+            return;
+        }
+        self.restrict_launch = true;
+
+        self.downloaders
+            .push(Downloader::new(self.downloaders.len()));
+        let index = self.downloaders.len() - 1;
+
+        match java::get_launcher_java_major(runtime) {
+            Some(java) => self.downloaders[index].start_java(java),
+            None => self.downloaders[index].start_java_runtime(runtime.to_owned()),
+        }
+    }
+
+    fn export_launch_script(&mut self) {
+        let game_settings = self.get_game_settings();
+        let token = match self.redact_launch_script_token {
+            true => Some(game_settings.account.token.clone()),
+            false => None,
+        };
+        let script_name = format!("{}-{}", self.current_game_instance, self.current_version);
+
+        self.launch_script_text = match launcher::build_launch_command(game_settings) {
+            Ok(launch_command) => {
+                match launch_command.export_script(&script_name, token.as_deref()) {
+                    Ok(path) => format!("Launch script exported to {path}"),
+                    Err(e) => format!("Failed to write launch script: {e}"),
+                }
+            }
+            Err(e) => format!("Failed to build launch command: {e}"),
         };
-        self.launcher.start(game_settings);
-        self.logs.clear();
         self.current_account_mc_data.token = String::new();
     }
+
+    fn close_game(&self) {
+        match &self.game_proccess {
+            GameProcess::Running(process) => {
+                // fails when the game already exited
+                if let Err(e) = process.kill() {
+                    println!("Failed to close game: {e}")
+                }
+            }
+            GameProcess::Detached(pid) => {
+                // the pid may belong to another process if the game already exited
+                match launcher::get_running_game() {
+                    Some(running_game) if running_game.pid == *pid => {
+                        if let Err(e) = launcher::kill_process(*pid) {
+                            println!("Failed to close game: {e}")
+                        }
+                    }
+                    _ => println!("The game is not running anymore."),
+                }
+            }
+            // the game exited while the prompt was open
+            GameProcess::Null => {}
+        }
+    }
+
+    fn exit(&mut self) -> iced::Command<Message> {
+        self.launcher.state = LauncherState::Idle;
+        self.downloaders.clear();
+        window::close(Id::MAIN)
+    }
 }
 
 impl Application for Minelander {
@@ -317,12 +585,25 @@ impl Application for Minelander {
                 }
             }
         }
+        // detected runtimes are found by their path, their name has the full version
+        if currentjava.path.is_empty() {
+            currentjava.path = p["current_java_path"]
+                .as_str()
+                .unwrap_or_default()
+                .to_owned();
+        }
 
         jvmnames.push("Automatic".to_owned());
         jvmnames.push("System Java".to_owned());
-        jvmnames.push("Java 8 (Minelander)".to_owned());
-        jvmnames.push("Java 17 (Minelander)".to_owned());
-        jvmnames.push("Java 21 (Minelander)".to_owned());
+        let mut launcher_java = vec![8, 17, 21];
+        for java in java::get_installed_launcher_java() {
+            if !launcher_java.contains(&java) {
+                launcher_java.push(java);
+            }
+        }
+        for java in launcher_java {
+            jvmnames.push(get_launcher_java_name(java));
+        }
 
         // Get Java info
 
@@ -394,17 +675,44 @@ impl Application for Minelander {
                 .to_owned(),
         };
 
+        // downloads use the mirrors from the start
+        let mirrors: network::Mirrors =
+            serde_json::from_value(p["mirrors"].clone()).unwrap_or_default();
+        network::set_mirrors(mirrors.clone());
+
+        // and the proxy, an invalid one falls back to the default client
+        let network_settings: network::NetworkSettings =
+            serde_json::from_value(p["network"].clone()).unwrap_or_default();
+        let network_text = match network::set_network_settings(&network_settings) {
+            Ok(_) => String::new(),
+            Err(e) => e,
+        };
+
         let initial_screen = match is_first_launcher_use{
             true => Screen::GettingStarted,
             false => Screen::Main,
         };
 
+        // A detached game may still be running from the last time the launcher was open
+        let (running_version, launcher_state) = match launcher::get_running_game() {
+            Some(running_game) => (
+                running_game.version.clone(),
+                LauncherState::Attaching(running_game),
+            ),
+            None => (String::new(), LauncherState::Idle),
+        };
+
         (
             Minelander {
                 screen: initial_screen,
                 current_account: current_account,
                 current_version: p["current_version"].as_str().unwrap().to_owned(),
                 game_ram: p["game_ram"].as_f64().unwrap(),
+                auto_ram: get_instance_auto_ram(&p, p["current_game_instance"].as_str().unwrap()),
+                system_memory: launcher::get_system_memory(),
+                instance_mod_count: launcher::count_mods(&launcher::get_instance_dir(
+                    p["current_game_instance"].as_str().unwrap(),
+                )),
                 current_java_name: currentjava.name.clone(),
                 current_java: currentjava,
                 current_game_instance: p["current_game_instance"].as_str().unwrap().to_owned(),
@@ -413,7 +721,33 @@ impl Application for Minelander {
                     .as_str()
                     .unwrap()
                     .to_owned(),
-                show_all_versions_in_download_list: p["show_all_versions"].as_bool().unwrap(),
+                version_filter: serde_json::from_value(p["version_filter"].clone())
+                    .unwrap_or_default(),
+                download_concurrency: p["download_concurrency"].as_u64().unwrap_or(8) as usize,
+                mirrors,
+                network_settings,
+                network_text,
+                detach_game: p["detach_game"].as_bool().unwrap(),
+                jvm_presets: serde_json::from_value(p["jvm_presets"].clone()).unwrap_or_default(),
+                java_agents: get_instance_list(
+                    &p,
+                    p["current_game_instance"].as_str().unwrap(),
+                    "java_agents",
+                ),
+                system_properties: get_instance_list(
+                    &p,
+                    p["current_game_instance"].as_str().unwrap(),
+                    "system_properties",
+                ),
+                current_jvm_preset: get_instance_jvm_preset(
+                    &p,
+                    p["current_game_instance"].as_str().unwrap(),
+                ),
+                launcher: Launcher {
+                    state: launcher_state,
+                    version: running_version,
+                },
+                redact_launch_script_token: true,
                 java_name_list: jvmnames,
                 game_instance_list: new_game_instance_list,
                 needs_to_update_download_list: true,
@@ -427,6 +761,14 @@ impl Application for Minelander {
                     update_manager::check_launcher_updates(),
                     Message::CheckedUpdates,
                 ),
+                Command::perform(
+                    async {
+                        tokio::task::spawn_blocking(java::scan)
+                            .await
+                            .unwrap_or_default()
+                    },
+                    Message::DetectedJava,
+                ),
             ]),
         )
     }
@@ -438,6 +780,12 @@ impl Application for Minelander {
     fn update(&mut self, message: Self::Message) -> iced::Command<Self::Message> {
         match message {
             Message::Launch => {
+                // the path of a detected Java is only known after the scan
+                if !self.java_detected && self.is_detected_java_selected() {
+                    self.game_state_text =
+                        String::from("Looking for installed Java, try again in a moment.");
+                    return Command::none();
+                }
                 if !self.restrict_launch
                     && !self.current_account.username.is_empty()
                     && !self.current_version.is_empty()
@@ -469,32 +817,23 @@ impl Application for Minelander {
                     launcher::Progress::Checked(missing) => {
                         if let Some(missing) = missing {
                             match missing {
-                                launcher::Missing::Java8 => {
+                                launcher::Missing::Java(java) => {
                                     self.launcher.state = LauncherState::Waiting;
                                     self.downloaders.push(Downloader {
                                         state: DownloaderState::Idle,
                                         id: self.downloaders.len(),
                                     });
                                     let index = self.downloaders.len() - 1;
-                                    self.downloaders[index].start_java(downloader::Java::J8)
+                                    self.downloaders[index].start_java(java)
                                 }
-                                launcher::Missing::Java17 => {
+                                launcher::Missing::JavaRuntime(component) => {
                                     self.launcher.state = LauncherState::Waiting;
                                     self.downloaders.push(Downloader {
                                         state: DownloaderState::Idle,
                                         id: self.downloaders.len(),
                                     });
                                     let index = self.downloaders.len() - 1;
-                                    self.downloaders[index].start_java(downloader::Java::J17)
-                                }
-                                launcher::Missing::Java21 => {
-                                    self.launcher.state = LauncherState::Waiting;
-                                    self.downloaders.push(Downloader {
-                                        state: DownloaderState::Idle,
-                                        id: self.downloaders.len(),
-                                    });
-                                    let index = self.downloaders.len() - 1;
-                                    self.downloaders[index].start_java(downloader::Java::J21)
+                                    self.downloaders[index].start_java_runtime(component)
                                 }
                                 launcher::Missing::VersionFiles(vec) => {
                                     self.game_state_text =
@@ -505,7 +844,8 @@ impl Application for Minelander {
                                         id: self.downloaders.len(),
                                     });
                                     let index = self.downloaders.len() - 1;
-                                    self.downloaders[index].start_missing_files(vec)
+                                    self.downloaders[index]
+                                        .start_missing_files(vec, self.download_concurrency)
                                 }
                                 launcher::Missing::VanillaJson(ver, folder) => {
                                     self.launcher.state = LauncherState::Waiting;
@@ -517,7 +857,7 @@ impl Application for Minelander {
                                                 &downloader::VersionType::Vanilla,
                                                 &ver,
                                                 &folder,
-                                                &reqwest::Client::new(),
+                                                &network::client(),
                                             )
                                             .await
                                             {
@@ -536,13 +876,28 @@ impl Application for Minelander {
                         self.game_proccess = GameProcess::Running(child);
                         self.game_state_text = String::new()
                     }
+                    launcher::Progress::StartedDetached(child, log_file) => {
+                        self.launcher.state = LauncherState::GettingLogs;
+                        self.game_proccess = GameProcess::Running(child);
+                        self.game_log_file = log_file;
+                        self.game_state_text = String::new()
+                    }
+                    launcher::Progress::Attached(running_game) => {
+                        self.launcher.state = LauncherState::GettingLogs;
+                        self.game_proccess = GameProcess::Detached(running_game.pid);
+                        self.game_log_file = running_game.log_file;
+                        self.game_state_text =
+                            format!("Attached to running game ({}).", running_game.version)
+                    }
                     launcher::Progress::GotLog(log) => {
                         self.logs.push(log);
                     }
                     launcher::Progress::Finished => {
                         self.game_state_text = String::new();
                         self.launcher.state = LauncherState::Idle;
+                        self.game_proccess = GameProcess::Null;
                     }
+                    launcher::Progress::Warned(warning) => self.game_state_text_2 = warning,
                     launcher::Progress::Errored(e) => {
                         self.game_state_text = e;
                         self.launcher.state = LauncherState::Idle;
@@ -557,14 +912,15 @@ impl Application for Minelander {
                 Command::none()
             }
             Message::ChangeScreen(new_screen) => {
-                if self.screen == Screen::Settings {
+                if self.screen == Screen::Settings || self.screen == Screen::ModifyCommand {
                     updatesettingsfile(
                         self.game_ram,
                         self.current_java_name.clone(),
+                        self.current_java.path.clone(),
                         self.current_game_instance.clone(),
                         self.game_wrapper_commands.clone(),
                         self.game_enviroment_variables.clone(),
-                        self.show_all_versions_in_download_list,
+                        self.detach_game,
                     )
                     .unwrap();
                 }
@@ -580,20 +936,31 @@ impl Application for Minelander {
                     Screen::Installation => {
                         if !self.vanilla_versions_download_list.is_empty()
                             || !self.fabric_versions_download_list.is_empty()
+                            || !self.quilt_versions_download_list.is_empty()
+                            || !self.forge_versions_download_list.is_empty()
+                            || !self.neoforge_versions_download_list.is_empty()
                             || self.needs_to_update_download_list
                         {
-                            let show_all_versions = self.show_all_versions_in_download_list;
                             return Command::perform(
-                                async move {
-                                    downloader::get_downloadable_version_list(show_all_versions)
-                                        .await
-                                },
+                                downloader::get_downloadable_version_list(),
                                 Message::GotDownloadList,
                             );
                         } else {
                             Command::none()
                         }
                     }
+                    Screen::Settings => {
+                        // mods may have been added since the instance was selected
+                        self.instance_mod_count = launcher::count_mods(
+                            &launcher::get_instance_dir(&self.current_game_instance),
+                        );
+                        Command::none()
+                    }
+                    Screen::JavaRuntimes => self.load_java_runtimes(),
+                    Screen::Versions => {
+                        self.version_to_uninstall = None;
+                        Command::perform(launcher::getinstalledversions(), Message::LoadVersionList)
+                    }
                     Screen::MicrosoftAccount => {
                         self.auth_status = String::from("Getting code and link...");
                         Command::perform(
@@ -630,14 +997,22 @@ impl Application for Minelander {
 
                 if selected_jvm_name.as_str() == "System Java"
                     || selected_jvm_name.as_str() == "Automatic"
-                    || selected_jvm_name.as_str() == "Java 8 (Minelander)"
-                    || selected_jvm_name.as_str() == "Java 17 (Minelander)"
-                    || selected_jvm_name.as_str() == "Java 21 (Minelander)"
+                    || parse_launcher_java_name(&selected_jvm_name).is_some()
                 {
                     newjvm.push(selected_jvm_name.clone());
                     newjvm.push(String::new());
                     newjvm.push(String::new());
 
+                    newjvmname = selected_jvm_name;
+                } else if let Some(info) = self
+                    .detected_java
+                    .iter()
+                    .find(|info| java::get_display_name(info) == selected_jvm_name)
+                {
+                    newjvm.push(selected_jvm_name.clone());
+                    newjvm.push(info.path.clone());
+                    newjvm.push(String::new());
+
                     newjvmname = selected_jvm_name;
                 } else {
                     let mut file = File::open(get_config_file_path()).unwrap();
@@ -667,42 +1042,132 @@ impl Application for Minelander {
                 };
                 Command::none()
             }
+            Message::DetectedJava(runtimes) => {
+                for info in &runtimes {
+                    let name = java::get_display_name(info);
+                    if !self.java_name_list.contains(&name) {
+                        self.java_name_list.push(name);
+                    }
+                }
+
+                // a JDK update changes the name of the saved runtime and sometimes its path
+                if self.is_detected_java_selected() {
+                    let selected_java = runtimes
+                        .iter()
+                        .find(|info| info.path == self.current_java.path)
+                        .or_else(|| {
+                            runtimes
+                                .iter()
+                                .find(|info| java::is_same_runtime(info, &self.current_java_name))
+                        });
+                    if let Some(info) = selected_java {
+                        self.current_java_name = java::get_display_name(info);
+                        self.current_java.name = self.current_java_name.clone();
+                        self.current_java.path = info.path.clone();
+                    }
+                }
+
+                self.detected_java = runtimes;
+                self.java_detected = true;
+                Command::none()
+            }
             Message::GameInstanceChanged(new_game_instance) => {
+                set_current_dir(env::current_exe().unwrap().parent().unwrap()).unwrap();
+
+                let data = getjson(get_config_file_path());
+                self.current_jvm_preset = get_instance_jvm_preset(&data, &new_game_instance);
+                self.auto_ram = get_instance_auto_ram(&data, &new_game_instance);
+                self.java_agents = get_instance_list(&data, &new_game_instance, "java_agents");
+                self.system_properties =
+                    get_instance_list(&data, &new_game_instance, "system_properties");
+                self.instance_mod_count =
+                    launcher::count_mods(&launcher::get_instance_dir(&new_game_instance));
                 self.current_game_instance = new_game_instance;
                 Command::none()
             }
+            Message::JvmPresetChanged(preset) => {
+                self.current_jvm_preset = preset;
+                update_instance_settings(
+                    &self.current_game_instance,
+                    "jvm_preset",
+                    Value::String(self.current_jvm_preset.clone()),
+                )
+                .unwrap();
+                Command::none()
+            }
             Message::GameRamChanged(new_ram) => {
                 self.game_ram = new_ram;
                 Command::none()
             }
+            Message::AutoRamChanged(auto_ram) => {
+                self.auto_ram = auto_ram;
+                update_instance_settings(
+                    &self.current_game_instance,
+                    "auto_ram",
+                    Value::Bool(auto_ram),
+                )
+                .unwrap();
+                Command::none()
+            }
             Message::GameWrapperCommandsChanged(s) => {
                 self.game_wrapper_commands = s;
                 Command::none()
             }
-            Message::ShowAllVersionsInDownloadListChanged(bool) => {
-                self.needs_to_update_download_list = true;
-                self.show_all_versions_in_download_list = bool;
-                Command::perform(
-                    async move { downloader::get_downloadable_version_list(bool).await },
-                    Message::GotDownloadList,
-                )
+            Message::DetachGameChanged(bool) => {
+                self.detach_game = bool;
+                Command::none()
+            }
+            Message::DownloadConcurrencyChanged(concurrency) => {
+                self.download_concurrency = concurrency as usize;
+                update_setting("download_concurrency", serde_json::json!(concurrency)).unwrap();
+                Command::none()
+            }
+            Message::VersionFilterChanged(filter) => {
+                self.version_filter = filter;
+                update_setting("version_filter", serde_json::json!(filter)).unwrap();
+                Command::none()
+            }
+            Message::VersionSearchChanged(search) => {
+                self.version_search = search;
+                Command::none()
+            }
+            Message::MirrorsChanged(mirrors) => {
+                self.mirrors = mirrors;
+                Command::none()
+            }
+            Message::MirrorsApplied => {
+                network::set_mirrors(self.mirrors.clone());
+                update_setting("mirrors", serde_json::json!(self.mirrors)).unwrap();
+                Command::none()
+            }
+            Message::NetworkSettingsChanged(network_settings) => {
+                self.network_settings = network_settings;
+                Command::none()
+            }
+            Message::NetworkSettingsApplied => {
+                // the previous client stays in use until the settings are valid
+                self.network_text = match network::set_network_settings(&self.network_settings) {
+                    Ok(_) => String::new(),
+                    Err(e) => e,
+                };
+                update_setting("network", serde_json::json!(self.network_settings)).unwrap();
+                Command::none()
             }
             Message::GotDownloadList(result) => {
                 match result {
-                    Ok(list) => {
-                        self.needs_to_update_download_list = false;
-                        if !list.is_empty() {
-                            self.vanilla_versions_download_list.clear();
-                            self.fabric_versions_download_list.clear();
-                            for i in &list[0] {
-                                let ii = i;
-                                self.vanilla_versions_download_list.push(ii.to_string());
-                            }
-                            for i in &list[1] {
-                                let ii = i;
-                                self.fabric_versions_download_list.push(ii.to_string());
-                            }
+                    Ok(version_list) => {
+                        // offline lists are fetched again next time
+                        self.needs_to_update_download_list = version_list.offline_error.is_some();
+                        if let Some(err) = version_list.offline_error {
+                            println!("Version list is offline: {err}");
+                            self.download_text =
+                                String::from("Offline, showing cached vanilla versions.");
                         }
+                        self.vanilla_versions_download_list = version_list.vanilla;
+                        self.fabric_versions_download_list = version_list.fabric;
+                        self.quilt_versions_download_list = version_list.quilt;
+                        self.forge_versions_download_list = version_list.forge;
+                        self.neoforge_versions_download_list = version_list.neoforge;
                     }
                     Err(err) => self.download_text = err,
                 }
@@ -710,23 +1175,119 @@ impl Application for Minelander {
                 Command::none()
             }
             Message::VanillaVersionToDownloadChanged(new_version) => {
-                self.vanilla_version_to_download = new_version;
+                self.vanilla_version_to_download = new_version.id;
                 Command::none()
             }
             Message::FabricVersionToDownloadChanged(new_version) => {
-                self.fabric_version_to_download = new_version;
+                let new_version = new_version.id;
+                self.fabric_version_to_download = new_version.clone();
+                self.fabric_loader_list.clear();
+                self.fabric_loader_to_download = None;
+                Command::perform(
+                    downloader::get_fabric_loader_versions(new_version),
+                    Message::GotFabricLoaders,
+                )
+            }
+            Message::GotFabricLoaders(result) => {
+                match result {
+                    Ok(loaders) => {
+                        // newest stable loader by default
+                        self.fabric_loader_to_download = loaders
+                            .iter()
+                            .find(|loader| loader.stable)
+                            .or(loaders.first())
+                            .cloned();
+                        self.fabric_loader_list = loaders;
+                    }
+                    Err(err) => self.download_text = err,
+                }
+                Command::none()
+            }
+            Message::FabricLoaderToDownloadChanged(loader) => {
+                self.fabric_loader_to_download = Some(loader);
+                Command::none()
+            }
+            Message::QuiltVersionToDownloadChanged(new_version) => {
+                let new_version = new_version.id;
+                self.quilt_version_to_download = new_version.clone();
+                self.quilt_loader_list.clear();
+                self.quilt_loader_to_download = None;
+                Command::perform(
+                    downloader::get_quilt_loader_versions(new_version),
+                    Message::GotQuiltLoaders,
+                )
+            }
+            Message::GotQuiltLoaders(result) => {
+                match result {
+                    Ok(loaders) => {
+                        self.quilt_loader_to_download = loaders
+                            .iter()
+                            .find(|loader| loader.stable)
+                            .or(loaders.first())
+                            .cloned();
+                        self.quilt_loader_list = loaders;
+                    }
+                    Err(err) => self.download_text = err,
+                }
+                Command::none()
+            }
+            Message::QuiltLoaderToDownloadChanged(loader) => {
+                self.quilt_loader_to_download = Some(loader);
+                Command::none()
+            }
+            Message::ForgeLoaderChanged(loader) => {
+                self.forge_loader = loader;
+                self.forge_version_to_download = String::new();
+                self.forge_list.clear();
+                self.forge_to_download = None;
+                Command::none()
+            }
+            Message::ForgeVersionToDownloadChanged(new_version) => {
+                let new_version = new_version.id;
+                self.forge_version_to_download = new_version.clone();
+                self.forge_list.clear();
+                self.forge_to_download = None;
+                Command::perform(
+                    forge::get_forge_versions(self.forge_loader, new_version),
+                    Message::GotForgeVersions,
+                )
+            }
+            Message::GotForgeVersions(result) => {
+                match result {
+                    Ok(versions) => {
+                        self.forge_to_download = versions
+                            .iter()
+                            .find(|version| version.stable)
+                            .or(versions.first())
+                            .cloned();
+                        self.forge_list = versions;
+                    }
+                    Err(err) => self.download_text = err,
+                }
+                Command::none()
+            }
+            Message::ForgeToDownloadChanged(version) => {
+                self.forge_to_download = Some(version);
                 Command::none()
             }
             Message::InstallVersion(ver_type) => {
                 let version = match ver_type {
                     downloader::VersionType::Vanilla => self.vanilla_version_to_download.clone(),
-                    downloader::VersionType::Fabric => self.fabric_version_to_download.clone(),
+                    downloader::VersionType::Fabric(_) => self.fabric_version_to_download.clone(),
+                    downloader::VersionType::Quilt(_) => self.quilt_version_to_download.clone(),
+                    downloader::VersionType::Forge(_) | downloader::VersionType::NeoForge(_) => {
+                        self.forge_version_to_download.clone()
+                    }
                 };
                 self.downloaders
                     .push(Downloader::new(self.downloaders.len()));
 
                 let index = self.downloaders.len() - 1;
-                self.downloaders[index].start(version, ver_type);
+                let settings = downloader::DownloadSettings {
+                    concurrency: self.download_concurrency,
+                    java: self.get_installer_java(),
+                };
+                self.downloaders[index].start(version, ver_type, settings);
                 Command::none()
             }
             Message::JvmNameToAddChanged(name) => {
@@ -777,6 +1338,304 @@ impl Application for Minelander {
                 }
                 Command::none()
             }
+            Message::JvmPresetToEditChanged(name) => {
+                if let Some(preset) = self.jvm_presets.iter().find(|preset| preset.name == name) {
+                    self.jvm_preset_name = preset.name.clone();
+                    self.jvm_preset_args = preset.args.clone();
+                    self.jvm_preset_min_java = preset.min_java.to_string();
+                    self.jvm_preset_max_java = preset.max_java.to_string();
+                }
+                self.jvm_presets_text.clear();
+                Command::none()
+            }
+            Message::JvmPresetNameChanged(name) => {
+                self.jvm_preset_name = name;
+                Command::none()
+            }
+            Message::JvmPresetArgsChanged(args) => {
+                self.jvm_preset_args = args;
+                Command::none()
+            }
+            Message::JvmPresetMinJavaChanged(java) => {
+                self.jvm_preset_min_java = java;
+                Command::none()
+            }
+            Message::JvmPresetMaxJavaChanged(java) => {
+                self.jvm_preset_max_java = java;
+                Command::none()
+            }
+            Message::JvmPresetSaved => {
+                let name = self.jvm_preset_name.trim().to_owned();
+                if name.is_empty() || name == "Automatic" {
+                    self.jvm_presets_text = String::from("Invalid preset name.");
+                    return Command::none();
+                }
+
+                // empty fields mean no limit
+                let parse_java = |java: &str| match java.trim() {
+                    "" => Ok(0),
+                    java => java.parse::<u32>(),
+                };
+                let (min_java, max_java) = match (
+                    parse_java(&self.jvm_preset_min_java),
+                    parse_java(&self.jvm_preset_max_java),
+                ) {
+                    (Ok(min_java), Ok(max_java)) if max_java == 0 || min_java <= max_java => {
+                        (min_java, max_java)
+                    }
+                    _ => {
+                        self.jvm_presets_text = String::from("Invalid Java version range.");
+                        return Command::none();
+                    }
+                };
+
+                let preset = java::JvmPreset {
+                    name: name.clone(),
+                    args: self.jvm_preset_args.trim().to_owned(),
+                    min_java,
+                    max_java,
+                };
+                match self
+                    .jvm_presets
+                    .iter_mut()
+                    .find(|preset| preset.name == name)
+                {
+                    Some(existing) => *existing = preset,
+                    None => self.jvm_presets.push(preset),
+                }
+
+                self.jvm_presets_text = match save_jvm_presets(&self.jvm_presets) {
+                    Ok(_) => format!("Saved {name}."),
+                    Err(e) => format!("Failed to save presets: {e}"),
+                };
+                Command::none()
+            }
+            Message::JvmPresetDeleted => {
+                let name = self.jvm_preset_name.trim().to_owned();
+                if !self.jvm_presets.iter().any(|preset| preset.name == name) {
+                    self.jvm_presets_text = format!("There is no preset named {name}.");
+                    return Command::none();
+                }
+                self.jvm_presets.retain(|preset| preset.name != name);
+
+                // instances using it go back to automatic, save_jvm_presets updates the other ones
+                if self.current_jvm_preset == name {
+                    self.current_jvm_preset = String::from("Automatic");
+                }
+
+                self.jvm_presets_text = match save_jvm_presets(&self.jvm_presets) {
+                    Ok(_) => format!("Deleted {name}."),
+                    Err(e) => format!("Failed to save presets: {e}"),
+                };
+                Command::none()
+            }
+            Message::JavaToInstallChanged(java) => {
+                self.java_to_install = java;
+                Command::none()
+            }
+            Message::InstallJava => {
+                match self.java_to_install.trim().parse::<u32>() {
+                    Ok(java) if !self.restrict_launch => {
+                        self.restrict_launch = true;
+                        self.downloaders
+                            .push(Downloader::new(self.downloaders.len()));
+
+                        let index = self.downloaders.len() - 1;
+                        self.downloaders[index].start_java(java);
+                        self.game_state_text = format!("Preparing Java {java} download.");
+                    }
+                    Ok(_) => {}
+                    Err(_) => self.game_state_text = String::from("Type a Java version, e.g. 17."),
+                }
+                Command::none()
+            }
+            Message::LoadedJavaRuntimes(runtimes) => {
+                self.java_runtimes = runtimes;
+                Command::none()
+            }
+            Message::UpdateJavaRuntime(runtime) => {
+                self.java_runtimes_text = format!("Checking for {runtime} updates...");
+                Command::perform(
+                    downloader::get_latest_runtime_version(runtime.clone()),
+                    move |result| Message::CheckedJavaRuntimeUpdate(runtime.clone(), result),
+                )
+            }
+            Message::CheckedJavaRuntimeUpdate(runtime, result) => {
+                let installed_version = self
+                    .java_runtimes
+                    .iter()
+                    .find(|i| i.name == runtime)
+                    .map(|i| i.version.clone())
+                    .unwrap_or_default();
+
+                match result {
+                    Ok(latest_version) if latest_version == installed_version => {
+                        self.java_runtimes_text = format!("{runtime} is up to date.")
+                    }
+                    Ok(latest_version) => {
+                        self.java_runtimes_text =
+                            format!("Updating {runtime} to {latest_version}.");
+                        self.install_java_runtime(&runtime)
+                    }
+                    Err(e) => self.java_runtimes_text = e,
+                }
+                Command::none()
+            }
+            Message::ReinstallJavaRuntime(runtime) => {
+                self.java_runtimes_text = format!("Reinstalling {runtime}.");
+                self.install_java_runtime(&runtime);
+                Command::none()
+            }
+            Message::UninstallJavaRuntime(runtime) => {
+                if self
+                    .downloaders
+                    .iter()
+                    .any(|downloader| downloader.get_runtime().as_ref() == Some(&runtime))
+                {
+                    self.java_runtimes_text = format!("Wait for the {runtime} download to finish.");
+                    return Command::none();
+                }
+
+                let runtime_dir = format!(
+                    "{}/minelander_java/{}",
+                    launcher::get_minecraft_dir(),
+                    runtime
+                );
+
+                self.java_runtimes_text = match fs::remove_dir_all(runtime_dir) {
+                    Ok(_) => format!("{runtime} was uninstalled."),
+                    Err(e) => format!("Failed to uninstall {runtime}: {e}"),
+                };
+                self.load_java_runtimes()
+            }
+            Message::RepairVersion(version) => {
+                // installs don't restrict the launch but can write the same files
+                if self.restrict_launch || !self.downloaders.is_empty() {
+                    self.versions_text = String::from("Wait for the current download to finish.");
+                    return Command::none();
+                }
+                if self.launcher.is_using(&version) {
+                    self.versions_text = format!("Close {version} before repairing it.");
+                    return Command::none();
+                }
+                // the game can't start while its files are replaced
+                self.restrict_launch = true;
+                self.versions_text = format!("Verifying {version}...");
+
+                self.downloaders
+                    .push(Downloader::new(self.downloaders.len()));
+                let index = self.downloaders.len() - 1;
+                self.downloaders[index].start_repair(version, self.download_concurrency);
+                Command::none()
+            }
+            Message::UninstallVersion(version) => {
+                let dependents = launcher::get_dependent_versions(&self.all_versions, &version);
+                self.versions_text = match dependents.is_empty() {
+                    true => format!("Uninstall {version}?"),
+                    false => format!(
+                        "Uninstall {version}? {} inherit from it and will stop working.",
+                        dependents.join(", ")
+                    ),
+                };
+                self.version_to_uninstall = Some(version);
+                Command::none()
+            }
+            Message::UninstallVersionCancelled => {
+                self.version_to_uninstall = None;
+                self.versions_text = String::new();
+                Command::none()
+            }
+            Message::UninstallVersionConfirmed => {
+                let Some(version) = self.version_to_uninstall.take() else {
+                    return Command::none();
+                };
+                if self.restrict_launch || !self.downloaders.is_empty() {
+                    self.versions_text = String::from("Wait for the current download to finish.");
+                    return Command::none();
+                }
+                if self.launcher.is_using(&version) {
+                    self.versions_text = format!("Close {version} before uninstalling it.");
+                    return Command::none();
+                }
+
+                self.versions_text = match launcher::uninstall_version(&version) {
+                    Ok(_) => format!("{version} was uninstalled."),
+                    Err(e) => format!("Failed to uninstall {version}: {e}"),
+                };
+                if self.current_version == version {
+                    self.current_version = String::new();
+                }
+                self.unused_files = None;
+                Command::perform(launcher::getinstalledversions(), Message::LoadVersionList)
+            }
+            Message::FindUnusedFiles => {
+                // files of an install that is still downloading would look unused
+                if !self.downloaders.is_empty() {
+                    self.versions_text = String::from("Wait for the current download to finish.");
+                    return Command::none();
+                }
+
+                self.versions_text = String::from("Looking for unused files...");
+                Command::perform(
+                    async {
+                        tokio::task::spawn_blocking(downloader::get_unused_files)
+                            .await
+                            .unwrap_or_else(|e| Err(e.to_string()))
+                    },
+                    Message::GotUnusedFiles,
+                )
+            }
+            Message::GotUnusedFiles(result) => {
+                match result {
+                    Ok(unused_files) => {
+                        self.versions_text = match unused_files.files.len() {
+                            0 => String::from("No unused files found."),
+                            count => format!(
+                                "{} unused files, {} MiB can be freed.",
+                                count,
+                                unused_files.size / 1048576
+                            ),
+                        };
+                        if !unused_files.kept_libraries_for.is_empty() {
+                            self.versions_text.push_str(&format!(
+                                " Libraries are kept, the Forge installer of {} is missing.",
+                                unused_files.kept_libraries_for.join(", ")
+                            ));
+                        }
+                        self.unused_files = Some(unused_files);
+                    }
+                    Err(e) => {
+                        self.versions_text = e;
+                        self.unused_files = None;
+                    }
+                }
+                Command::none()
+            }
+            Message::DeleteUnusedFiles => {
+                if self.restrict_launch || !self.downloaders.is_empty() {
+                    self.versions_text = String::from("Wait for the current download to finish.");
+                    return Command::none();
+                }
+
+                // searched again, a version installed since the preview may use some of the files
+                self.versions_text = String::from("Deleting unused files...");
+                self.unused_files = None;
+                Command::perform(
+                    async {
+                        tokio::task::spawn_blocking(|| downloader::get_unused_files()?.delete())
+                            .await
+                            .unwrap_or_else(|e| Err(e.to_string()))
+                    },
+                    Message::DeletedUnusedFiles,
+                )
+            }
+            Message::DeletedUnusedFiles(result) => {
+                self.versions_text = match result {
+                    Ok(freed) => format!("Freed {} MiB.", freed / 1048576),
+                    Err(e) => format!("Failed to clean up: {e}"),
+                };
+                Command::none()
+            }
             Message::GameInstanceToAddChanged(game_prof) => {
                 self.game_instance_to_add = game_prof;
                 Command::none()
@@ -815,6 +1674,70 @@ impl Application for Minelander {
                 }
                 Command::none()
             }
+            Message::JavaAgentPathChanged(path) => {
+                self.java_agent_to_add.path = path;
+                Command::none()
+            }
+            Message::JavaAgentOptionsChanged(options) => {
+                self.java_agent_to_add.options = options;
+                Command::none()
+            }
+            Message::JavaAgentAdded => {
+                let agent = launcher::JavaAgent {
+                    path: self.java_agent_to_add.path.trim().replace('\\', "/"),
+                    options: self.java_agent_to_add.options.trim().to_owned(),
+                };
+                match launcher::check_java_agent(&agent.path) {
+                    Ok(_) => {
+                        self.java_agents.push(agent);
+                        self.java_agent_to_add = launcher::JavaAgent::default();
+                        self.save_java_agents();
+                    }
+                    Err(e) => self.java_agents_text = e,
+                }
+                Command::none()
+            }
+            Message::JavaAgentRemoved(index) => {
+                if index < self.java_agents.len() {
+                    self.java_agents.remove(index);
+                    self.save_java_agents();
+                }
+                Command::none()
+            }
+            Message::SystemPropertyKeyChanged(key) => {
+                self.system_property_to_add.key = key;
+                Command::none()
+            }
+            Message::SystemPropertyValueChanged(value) => {
+                self.system_property_to_add.value = value;
+                Command::none()
+            }
+            Message::SystemPropertyAdded => {
+                // users often paste the whole -Dkey
+                let key = self.system_property_to_add.key.trim();
+                let property = launcher::SystemProperty {
+                    key: key.strip_prefix("-D").unwrap_or(key).to_owned(),
+                    value: self.system_property_to_add.value.clone(),
+                };
+                match launcher::check_system_property_key(&property.key) {
+                    Ok(_) => {
+                        // a property can only have one value
+                        self.system_properties.retain(|p| p.key != property.key);
+                        self.system_properties.push(property);
+                        self.system_property_to_add = launcher::SystemProperty::default();
+                        self.save_java_agents();
+                    }
+                    Err(e) => self.java_agents_text = e,
+                }
+                Command::none()
+            }
+            Message::SystemPropertyRemoved(index) => {
+                if index < self.system_properties.len() {
+                    self.system_properties.remove(index);
+                    self.save_java_agents();
+                }
+                Command::none()
+            }
 
             Message::ManageDownload((id, progress)) => {
                 match progress {
@@ -823,18 +1746,18 @@ impl Application for Minelander {
                             format!("Downloaded 0 from {} files. (0%)", file_number);
                         self.files_download_number = file_number;
                     }
-                    downloader::Progress::Downloaded(remaining_files_number) => {
-                        let downloaded_files = self.files_download_number - remaining_files_number;
-
-                        let percentage = (downloaded_files as f32
-                            / self.files_download_number as f32
-                            * 100.0) as i32;
+                    downloader::Progress::Downloaded(progress) => {
+                        let downloaded_files =
+                            self.files_download_number - progress.files_left as i32;
 
                         self.download_text = format!(
-                            "Downloaded {} from {} files. ({}%)",
-                            downloaded_files, self.files_download_number, percentage
+                            "Downloaded {} from {} files. {}",
+                            downloaded_files, self.files_download_number, progress
                         );
                     }
+                    downloader::Progress::RunningForgeProcessors => {
+                        self.download_text = String::from("Running Forge installer processors.");
+                    }
                     downloader::Progress::Finished => {
                         self.download_text = String::from("Version installed successfully.");
                         for (index, downloader) in self.downloaders.iter().enumerate() {
@@ -853,6 +1776,7 @@ impl Application for Minelander {
                     }
                     downloader::Progress::Errored(error) => {
                         self.download_text = format!("Failed to install: {error}");
+                        self.restrict_launch = false;
                         for (index, downloader) in self.downloaders.iter().enumerate() {
                             if downloader.id == id {
                                 self.downloaders.remove(index);
@@ -871,6 +1795,7 @@ impl Application for Minelander {
                             self.java_download_size
                         )
                     }
+                    downloader::Progress::JavaDownloadRetrying(text) => self.game_state_text = text,
                     downloader::Progress::JavaDownloadFinished => {
                         self.game_state_text = String::from("Extracting Java")
                     }
@@ -884,12 +1809,26 @@ impl Application for Minelander {
                             }
                         }
 
-                        self.launch();
+                        for java in java::get_installed_launcher_java() {
+                            let name = get_launcher_java_name(java);
+                            if !self.java_name_list.contains(&name) {
+                                self.java_name_list.push(name);
+                            }
+                        }
+
+                        // Java installed from the Java screens doesn't start the game
+                        if self.launcher.state == LauncherState::Waiting {
+                            self.launch();
+                        } else if self.screen == Screen::JavaRuntimes {
+                            return self.load_java_runtimes();
+                        }
                     }
-                    downloader::Progress::MissingFilesDownloadProgressed(missing_files) => {
+                    downloader::Progress::MissingFilesDownloadProgressed(progress) => {
                         self.restrict_launch = true;
-                        self.game_state_text =
-                            format!("Downloading missing files. {} left", missing_files);
+                        self.game_state_text = format!(
+                            "Downloading missing files. {} left, {}",
+                            progress.files_left, progress
+                        );
                     }
                     downloader::Progress::MissingFilesDownloadFinished => {
                         self.restrict_launch = false;
@@ -902,6 +1841,35 @@ impl Application for Minelander {
 
                         self.launch();
                     }
+                    downloader::Progress::RepairVerifying(checked, total) => {
+                        self.versions_text = format!("Verifying files. {checked} / {total}");
+                    }
+                    downloader::Progress::RepairDownloadProgressed(progress) => {
+                        self.versions_text = format!(
+                            "Downloading missing and corrupt files. {} left, {}",
+                            progress.files_left, progress
+                        );
+                    }
+                    downloader::Progress::RepairFinished(summary) => {
+                        self.versions_text = summary.to_string();
+                        self.restrict_launch = false;
+                        for (index, downloader) in self.downloaders.iter().enumerate() {
+                            if downloader.id == id {
+                                self.downloaders.remove(index);
+                                break;
+                            }
+                        }
+                    }
+                    downloader::Progress::RepairErrored(error) => {
+                        self.versions_text = format!("Failed to repair: {error}");
+                        self.restrict_launch = false;
+                        for (index, downloader) in self.downloaders.iter().enumerate() {
+                            if downloader.id == id {
+                                self.downloaders.remove(index);
+                                break;
+                            }
+                        }
+                    }
                     downloader::Progress::UpdateStarted(total) => {
                         self.update_text = format!("Downloading update. 0 / {total} MiB (0%)")
                     }
@@ -964,19 +1932,61 @@ impl Application for Minelander {
                 Command::none()
             }
             Message::Exit => {
-                self.launcher.state = LauncherState::Idle;
-                self.downloaders.clear();
-                window::close(Id::MAIN)
+                if self.launcher.state == LauncherState::GettingLogs
+                    && self.screen != Screen::ExitPrompt
+                {
+                    self.screen = Screen::ExitPrompt;
+                    return Command::none();
+                }
+                self.exit()
             }
-            Message::CloseGame => {
-                match &self.game_proccess {
-                    GameProcess::Running(process) => match process.kill() {
-                        Ok(ok) => ok,
-                        Err(e) => panic!("{}", e),
-                    },
-                    GameProcess::Null => todo!(),
+            Message::RedactLaunchScriptTokenChanged(bool) => {
+                self.redact_launch_script_token = bool;
+                Command::none()
+            }
+            Message::ExportLaunchScript => {
+                if self.current_account.username.is_empty() || self.current_version.is_empty() {
+                    self.launch_script_text =
+                        String::from("Select an account and a version first.");
+                    return Command::none();
                 }
 
+                if self.current_account.microsoft {
+                    self.launch_script_text = String::from("Fetching account data...");
+
+                    return Command::perform(
+                        auth::login_with_refresh_token(self.current_account.refresh_token.clone()),
+                        Message::ExportLaunchScriptWithAccount,
+                    );
+                }
+
+                self.current_account_mc_data = auth::MinecraftAccount {
+                    username: self.current_account.username.clone(),
+                    token: "[pro]".to_string(),
+                    uuid: String::new(),
+                };
+                self.export_launch_script();
+                Command::none()
+            }
+            Message::ExportLaunchScriptWithAccount(mc_account) => {
+                match mc_account {
+                    Some(mc_account) => self.current_account_mc_data = mc_account,
+                    None => {
+                        self.current_account_mc_data.username =
+                            self.current_account.username.clone()
+                    }
+                }
+
+                self.export_launch_script();
+                Command::none()
+            }
+            Message::ExitKeepingGame => self.exit(),
+            Message::ExitClosingGame => {
+                self.close_game();
+                self.exit()
+            }
+            Message::CloseGame => {
+                self.close_game();
                 Command::none()
             }
             Message::OpenURL(url) => {
@@ -1284,6 +2294,136 @@ impl Application for Minelander {
     }
 }
 
+// Java downloaded by Minelander is shown as "Java <major version> (Minelander)".
+fn get_launcher_java_name(java: u32) -> String {
+    format!("Java {} (Minelander)", java)
+}
+
+fn parse_launcher_java_name(name: &str) -> Option<u32> {
+    name.strip_prefix("Java ")?
+        .strip_suffix(" (Minelander)")?
+        .parse()
+        .ok()
+}
+
+// Checks a version without starting the game, useful for CI.
+// Usage: minelander --dry-run [--version <version>] [--instance <instance>] [--java <JVM name>]
+fn dry_run(args: &[String]) -> i32 {
+    // the settings are only read, nothing is written or created
+    let p: Value = match fs::read_to_string(get_config_file_path()) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+        Err(_) => Value::Null,
+    };
+
+    let mut version = p["current_version"].as_str().unwrap_or_default().to_owned();
+    let mut instance = p["current_game_instance"]
+        .as_str()
+        .unwrap_or("Default")
+        .to_owned();
+    let mut java_name = p["current_java_name"]
+        .as_str()
+        .unwrap_or("Automatic")
+        .to_owned();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.clone().next()) {
+            ("--version", Some(value)) => version = value.clone(),
+            ("--instance", Some(value)) => instance = value.clone(),
+            ("--java", Some(value)) => java_name = value.clone(),
+            _ => continue,
+        }
+        args.next();
+    }
+
+    if version.is_empty() {
+        eprintln!("No version selected, use --version <version>.");
+        return 2;
+    }
+
+    let Some(java) = get_dry_run_java(&p, &java_name) else {
+        eprintln!("Unknown JVM: {java_name}");
+        return 2;
+    };
+
+    let username = match p["current_account"]["username"].as_str() {
+        Some(username) if !username.is_empty() => username.to_owned(),
+        _ => String::from("Player"),
+    };
+
+    let minelander = Minelander {
+        current_version: version,
+        game_ram: p["game_ram"].as_f64().unwrap_or(2.5),
+        auto_ram: get_instance_auto_ram(&p, &instance),
+        current_java_name: java.name.clone(),
+        current_java: java,
+        game_wrapper_commands: p["game_wrapper_commands"]
+            .as_str()
+            .unwrap_or_default()
+            .to_owned(),
+        game_enviroment_variables: p["game_enviroment_variables"]
+            .as_str()
+            .unwrap_or_default()
+            .to_owned(),
+        detach_game: p["detach_game"].as_bool().unwrap_or(false),
+        jvm_presets: serde_json::from_value(p["jvm_presets"].clone()).unwrap_or_default(),
+        java_agents: get_instance_list(&p, &instance, "java_agents"),
+        system_properties: get_instance_list(&p, &instance, "system_properties"),
+        current_jvm_preset: get_instance_jvm_preset(&p, &instance),
+        current_game_instance: instance,
+        current_account_mc_data: auth::MinecraftAccount {
+            username,
+            token: "[pro]".to_string(),
+            uuid: String::new(),
+        },
+        ..Default::default()
+    };
+
+    let report = launcher::dry_run(minelander.get_game_settings());
+    println!("{report}");
+
+    match report.is_healthy() {
+        true => 0,
+        false => 1,
+    }
+}
+
+// Finds the JVM like the launcher does, Java is only scanned for when it is a detected one.
+fn get_dry_run_java(p: &Value, name: &str) -> Option<Java> {
+    let java = |path: &str, flags: &str| Java {
+        name: name.to_owned(),
+        path: path.to_owned(),
+        flags: flags.to_owned(),
+    };
+
+    if name == "Automatic" || name == "System Java" || parse_launcher_java_name(name).is_some() {
+        return Some(java("", ""));
+    }
+    if let Some(jvm) = p["JVMs"]
+        .as_array()
+        .and_then(|jvms| jvms.iter().find(|jvm| jvm["name"] == name))
+    {
+        return Some(java(
+            jvm["path"].as_str().unwrap_or_default(),
+            jvm["flags"].as_str().unwrap_or_default(),
+        ));
+    }
+    // detected Java is saved with its path
+    if p["current_java_name"] == name {
+        if let Some(path) = p["current_java_path"]
+            .as_str()
+            .filter(|path| !path.is_empty())
+        {
+            return Some(java(path, ""));
+        }
+    }
+
+    java::scan()
+        .into_iter()
+        .find(|info| java::get_display_name(info) == name)
+        .map(|info| java(&info.path, ""))
+}
+
 fn action<'a>(
     widget: Button<'a, Message, Theme, Renderer>,
     tp_text: &'a str,
@@ -1370,12 +2510,63 @@ fn checksettingsfile() -> bool {
             );
         }
 
-        if !map.contains_key("show_all_versions") {
+        if !map.contains_key("mirrors") {
             map.insert(
-                "show_all_versions".to_owned(),
+                "mirrors".to_owned(),
+                serde_json::to_value(network::Mirrors::default()).unwrap(),
+            );
+        }
+
+        if !map.contains_key("network") {
+            map.insert(
+                "network".to_owned(),
+                serde_json::to_value(network::NetworkSettings {
+                    connect_timeout: 15,
+                    ..Default::default()
+                })
+                .unwrap(),
+            );
+        }
+
+        if !map.contains_key("version_filter") {
+            // "show_all_versions" was the only filter before
+            let show_all_versions = map
+                .get("show_all_versions")
+                .and_then(|show_all| show_all.as_bool())
+                .unwrap_or(false);
+            let filter = downloader::VersionFilter {
+                snapshot: show_all_versions,
+                old_beta: show_all_versions,
+                old_alpha: show_all_versions,
+                ..Default::default()
+            };
+            map.insert(
+                "version_filter".to_owned(),
+                serde_json::to_value(filter).unwrap(),
+            );
+        }
+
+        if !map.contains_key("detach_game") {
+            map.insert(
+                "detach_game".to_owned(),
                 serde_json::to_value(false).unwrap(),
             );
         }
+
+        if !map.contains_key("download_concurrency") {
+            map.insert("download_concurrency".to_owned(), serde_json::json!(8));
+        }
+
+        if !map.contains_key("jvm_presets") {
+            map.insert(
+                "jvm_presets".to_owned(),
+                serde_json::to_value(java::get_default_jvm_presets()).unwrap(),
+            );
+        }
+
+        if !map.contains_key("instance_settings") {
+            map.insert("instance_settings".to_owned(), serde_json::json!({}));
+        }
     }
     let serializedjson = serde_json::to_string_pretty(&conf_json).unwrap();
 
@@ -1451,10 +2642,11 @@ fn save_account(account: Account) -> Vec<Account> {
 fn updatesettingsfile(
     ram: f64,
     currentjvm: String,
+    currentjvmpath: String,
     current_game_instance: String,
     wrapper_commands: String,
     env_variables: String,
-    showallversions: bool,
+    detach_game: bool,
 ) -> std::io::Result<()> {
     set_current_dir(env::current_exe().unwrap().parent().unwrap()).unwrap();
 
@@ -1466,10 +2658,100 @@ fn updatesettingsfile(
 
     data["game_ram"] = serde_json::Value::Number(Number::from_f64(ram).unwrap());
     data["current_java_name"] = serde_json::Value::String(currentjvm);
+    data["current_java_path"] = serde_json::Value::String(currentjvmpath);
     data["current_game_instance"] = serde_json::Value::String(current_game_instance);
     data["game_wrapper_commands"] = serde_json::Value::String(wrapper_commands);
-    data["show_all_versions"] = serde_json::Value::Bool(showallversions);
     data["game_enviroment_variables"] = serde_json::Value::String(env_variables);
+    data["detach_game"] = serde_json::Value::Bool(detach_game);
+
+    let serialized = serde_json::to_string_pretty(&data)?;
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(get_config_file_path())?;
+    file.write_all(serialized.as_bytes())?;
+
+    Ok(())
+}
+
+fn update_setting(key: &str, value: Value) -> std::io::Result<()> {
+    set_current_dir(env::current_exe().unwrap().parent().unwrap()).unwrap();
+
+    let mut data = getjson(get_config_file_path());
+    data[key] = value;
+
+    let serialized = serde_json::to_string_pretty(&data)?;
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(get_config_file_path())?;
+    file.write_all(serialized.as_bytes())?;
+
+    Ok(())
+}
+
+// Settings that only apply to one game instance, stored as "instance_settings": {"<instance>": {...}}.
+fn update_instance_settings(instance: &str, key: &str, value: Value) -> std::io::Result<()> {
+    set_current_dir(env::current_exe().unwrap().parent().unwrap()).unwrap();
+
+    let mut data = getjson(get_config_file_path());
+
+    if !data["instance_settings"][instance].is_object() {
+        data["instance_settings"][instance] = serde_json::json!({});
+    }
+    data["instance_settings"][instance][key] = value;
+
+    let serialized = serde_json::to_string_pretty(&data)?;
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(get_config_file_path())?;
+    file.write_all(serialized.as_bytes())?;
+
+    Ok(())
+}
+
+fn get_instance_jvm_preset(data: &Value, instance: &str) -> String {
+    data["instance_settings"][instance]["jvm_preset"]
+        .as_str()
+        .unwrap_or("Automatic")
+        .to_owned()
+}
+
+fn get_instance_auto_ram(data: &Value, instance: &str) -> bool {
+    data["instance_settings"][instance]["auto_ram"]
+        .as_bool()
+        .unwrap_or(false)
+}
+
+fn get_instance_list<T: serde::de::DeserializeOwned>(
+    data: &Value,
+    instance: &str,
+    key: &str,
+) -> Vec<T> {
+    serde_json::from_value(data["instance_settings"][instance][key].clone()).unwrap_or_default()
+}
+
+fn save_jvm_presets(presets: &[java::JvmPreset]) -> std::io::Result<()> {
+    set_current_dir(env::current_exe().unwrap().parent().unwrap()).unwrap();
+
+    let mut data = getjson(get_config_file_path());
+    data["jvm_presets"] = serde_json::to_value(presets)?;
+
+    // instances using a preset that no longer exists go back to automatic
+    if let Some(instances) = data["instance_settings"].as_object_mut() {
+        for settings in instances.values_mut() {
+            let deleted = settings["jvm_preset"].as_str().is_some_and(|name| {
+                name != "Automatic" && !presets.iter().any(|preset| preset.name == name)
+            });
+            if deleted {
+                settings["jvm_preset"] = Value::String(String::from("Automatic"));
+            }
+        }
+    }
 
     let serialized = serde_json::to_string_pretty(&data)?;
 
@@ -1488,31 +2770,42 @@ fn updatesettingsfile(
 #[derive(Debug)]
 struct Launcher {
     state: LauncherState,
+    // the version that is launching or running
+    version: String,
 }
 #[derive(Debug, PartialEq)]
 enum LauncherState {
     Idle,
     Waiting,
     Launching(Box<launcher::GameSettings>),
+    Attaching(launcher::RunningGame),
     GettingLogs,
 }
 impl Default for Launcher {
     fn default() -> Self {
         Launcher {
             state: LauncherState::Idle,
+            version: String::new(),
         }
     }
 }
 impl Launcher {
     pub fn start(&mut self, game_settings: launcher::GameSettings) {
+        self.version = game_settings.game_version.clone();
         self.state = LauncherState::Launching(Box::new(game_settings))
     }
+    pub fn is_using(&self, version: &str) -> bool {
+        self.state != LauncherState::Idle && self.version == version
+    }
     pub fn subscription(&self) -> Subscription<Message> {
         match &self.state {
             LauncherState::Idle => Subscription::none(),
             LauncherState::Launching(game_settings) => {
                 launcher::start(0, Some(game_settings)).map(Message::ManageGameInfo)
             }
+            LauncherState::Attaching(running_game) => {
+                launcher::attach(0, running_game.clone()).map(Message::ManageGameInfo)
+            }
             LauncherState::GettingLogs => launcher::start(0, None).map(Message::ManageGameInfo),
             LauncherState::Waiting => Subscription::none(),
         }
@@ -1526,9 +2819,16 @@ struct Downloader {
 }
 enum DownloaderState {
     Idle,
-    Downloading(String, downloader::VersionType),
-    JavaDownloading(downloader::Java),
+    Downloading(
+        String,
+        downloader::VersionType,
+        downloader::DownloadSettings,
+    ),
+    JavaDownloading(u32),
+    JavaRuntimeDownloading(String),
     DownloadingMissingFiles(downloader::DownloadList),
+    // version, concurrency
+    Repairing(String, usize),
     Update(String),
 }
 
@@ -1548,36 +2848,67 @@ impl Downloader {
         }
     }
 
-    pub fn start(&mut self, version: String, version_type: downloader::VersionType) {
-        self.state = DownloaderState::Downloading(version, version_type)
+    pub fn start(
+        &mut self,
+        version: String,
+        version_type: downloader::VersionType,
+        settings: downloader::DownloadSettings,
+    ) {
+        self.state = DownloaderState::Downloading(version, version_type, settings)
     }
-    pub fn start_java(&mut self, java: downloader::Java) {
+    pub fn start_java(&mut self, java: u32) {
         self.state = DownloaderState::JavaDownloading(java)
     }
+    pub fn start_java_runtime(&mut self, component: String) {
+        self.state = DownloaderState::JavaRuntimeDownloading(component)
+    }
     pub fn start_update(&mut self, url: String) {
         self.state = DownloaderState::Update(url)
     }
-    pub fn start_missing_files(&mut self, files: Vec<downloader::Download>) {
+    pub fn start_missing_files(&mut self, files: Vec<downloader::Download>, concurrency: usize) {
         let download_list = downloader::DownloadList {
             download_list: files,
-            client: reqwest::Client::new(),
+            client: network::client(),
+            concurrency,
         };
         self.state = DownloaderState::DownloadingMissingFiles(download_list)
     }
+    // The minelander_java folder this downloader writes to.
+    pub fn get_runtime(&self) -> Option<String> {
+        match &self.state {
+            DownloaderState::JavaDownloading(java) => Some(format!("java{}", java)),
+            DownloaderState::JavaRuntimeDownloading(component) => Some(component.clone()),
+            _ => None,
+        }
+    }
+    pub fn start_repair(&mut self, version: String, concurrency: usize) {
+        self.state = DownloaderState::Repairing(version, concurrency)
+    }
     pub fn subscription(&self) -> Subscription<Message> {
         match &self.state {
             DownloaderState::Idle => Subscription::none(),
-            DownloaderState::Downloading(version, version_type) => {
-                downloader::start(self.id, version.to_string(), version_type.clone())
-                    .map(Message::ManageDownload)
-            }
+            DownloaderState::Downloading(version, version_type, settings) => downloader::start(
+                self.id,
+                version.to_string(),
+                version_type.clone(),
+                settings.clone(),
+            )
+            .map(Message::ManageDownload),
             DownloaderState::JavaDownloading(java) => {
-                downloader::start_java(self.id, java.clone()).map(Message::ManageDownload)
+                downloader::start_java(self.id, *java).map(Message::ManageDownload)
+            }
+            DownloaderState::JavaRuntimeDownloading(component) => {
+                downloader::start_java_runtime(self.id, component.clone())
+                    .map(Message::ManageDownload)
             }
             DownloaderState::DownloadingMissingFiles(download_list) => {
                 downloader::start_missing_files(self.id, download_list.clone())
                     .map(Message::ManageDownload)
             }
+            DownloaderState::Repairing(version, concurrency) => {
+                downloader::start_repair(self.id, version.clone(), *concurrency)
+                    .map(Message::ManageDownload)
+            }
             DownloaderState::Update(url) => {
                 downloader::start_update(self.id, url.to_string()).map(Message::ManageDownload)
             }
@@ -1621,16 +2952,6 @@ fn get_config_file_path() -> String {
     return format!("{}/minelander_settings.json", launcher::get_minecraft_dir());
 }
 
-fn is_file_empty(file_path: &str) -> bool {
-    let mut file = File::open(file_path).unwrap();
-    let mut buffer = [0; 1];
-
-    match file.read(&mut buffer).unwrap() {
-        0 => true,
-        _ => false,
-    }
-}
-
 fn backward_compatibility_measures() {
     let old_game_instances_path = format!("{}/minelander_profiles", get_minecraft_dir());
     let new_game_instances_path = format!("{}/minelander_instances", get_minecraft_dir());
@@ -0,0 +1,229 @@
+use reqwest::{Client, Proxy, RequestBuilder, Response, Url};
+use serde::{Deserialize, Serialize};
+use std::{sync::RwLock, time::Duration};
+
+// Proxy, user agent and timeouts used by every request, timeouts are in seconds and 0 disables them.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct NetworkSettings {
+    pub proxy: String,
+    pub proxy_username: String,
+    pub proxy_password: String,
+    pub user_agent: String,
+    pub connect_timeout: u16,
+    pub timeout: u16,
+}
+
+impl NetworkSettings {
+    pub fn get_user_agent(&self) -> String {
+        match self.user_agent.trim() {
+            "" => format!("minelander/{}", env!("CARGO_PKG_VERSION")),
+            user_agent => user_agent.to_owned(),
+        }
+    }
+
+    // Java system properties for the same proxy, used by the Forge installer processors.
+    pub fn get_java_proxy_args(&self) -> Vec<String> {
+        let Ok(proxy_url) = Url::parse(self.proxy.trim()) else {
+            return vec![];
+        };
+        let Some(host) = proxy_url.host_str() else {
+            return vec![];
+        };
+
+        match proxy_url.scheme() {
+            "http" | "https" => {
+                let port = proxy_url.port_or_known_default().unwrap_or(80);
+                // Java has no properties for http proxy credentials
+                vec![
+                    format!("-Dhttp.proxyHost={host}"),
+                    format!("-Dhttp.proxyPort={port}"),
+                    format!("-Dhttps.proxyHost={host}"),
+                    format!("-Dhttps.proxyPort={port}"),
+                ]
+            }
+            "socks5" | "socks5h" => {
+                let mut args = vec![
+                    format!("-DsocksProxyHost={host}"),
+                    format!("-DsocksProxyPort={}", proxy_url.port().unwrap_or(1080)),
+                ];
+                if !self.proxy_username.is_empty() {
+                    args.push(format!("-Djava.net.socks.username={}", self.proxy_username));
+                    args.push(format!("-Djava.net.socks.password={}", self.proxy_password));
+                }
+                args
+            }
+            _ => vec![],
+        }
+    }
+
+    fn build_client(&self) -> Result<Client, String> {
+        let mut builder = Client::builder().user_agent(self.get_user_agent());
+
+        if self.connect_timeout > 0 {
+            builder = builder.connect_timeout(Duration::from_secs(self.connect_timeout.into()));
+        }
+        if self.timeout > 0 {
+            builder = builder.timeout(Duration::from_secs(self.timeout.into()));
+        }
+
+        // Without a proxy reqwest uses HTTP_PROXY, HTTPS_PROXY and ALL_PROXY from the environment.
+        if !self.proxy.trim().is_empty() {
+            let mut proxy_url = match Url::parse(self.proxy.trim()) {
+                Ok(ok) => ok,
+                Err(e) => return Err(format!("Invalid proxy url: {e}")),
+            };
+
+            // Credentials go in the url so they also work with socks5.
+            if !self.proxy_username.is_empty()
+                && (proxy_url.set_username(&self.proxy_username).is_err()
+                    || proxy_url.set_password(Some(&self.proxy_password)).is_err())
+            {
+                return Err(String::from(
+                    "Invalid proxy url: credentials are not supported",
+                ));
+            }
+
+            let proxy = match Proxy::all(proxy_url) {
+                Ok(ok) => ok,
+                Err(e) => return Err(format!("Invalid proxy: {e}")),
+            };
+            builder = builder.proxy(proxy);
+        }
+
+        match builder.build() {
+            Ok(ok) => Ok(ok),
+            Err(e) => Err(format!("Failed to create the network client: {e}")),
+        }
+    }
+}
+
+static CLIENT: RwLock<Option<Client>> = RwLock::new(None);
+static JAVA_PROXY_ARGS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+// Replaces the shared client, the previous one is kept if the settings are invalid.
+pub fn set_network_settings(settings: &NetworkSettings) -> Result<(), String> {
+    let client = settings.build_client()?;
+
+    match CLIENT.write() {
+        Ok(mut ok) => *ok = Some(client),
+        Err(e) => return Err(format!("Failed to set the network client: {e}")),
+    }
+    if let Ok(mut ok) = JAVA_PROXY_ARGS.write() {
+        *ok = settings.get_java_proxy_args();
+    }
+
+    Ok(())
+}
+
+pub fn get_java_proxy_args() -> Vec<String> {
+    match JAVA_PROXY_ARGS.read() {
+        Ok(ok) => ok.clone(),
+        Err(_) => vec![],
+    }
+}
+
+// Client shared by every request, clones share the same connection pool.
+pub fn client() -> Client {
+    if let Ok(client) = CLIENT.read() {
+        if let Some(client) = client.as_ref() {
+            return client.clone();
+        }
+    }
+
+    let client = NetworkSettings::default()
+        .build_client()
+        .unwrap_or_default();
+
+    if let Ok(mut ok) = CLIENT.write() {
+        *ok = Some(client.clone());
+    }
+
+    client
+}
+
+// Base urls that replace the official servers, empty ones are not replaced.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Mirrors {
+    pub meta: String,
+    pub assets: String,
+    pub libraries: String,
+    pub fabric: String,
+}
+
+impl Mirrors {
+    // BMCLAPI mirrors everything with the official paths.
+    pub fn bmclapi() -> Self {
+        Mirrors {
+            meta: String::from("https://bmclapi2.bangbang93.com"),
+            assets: String::from("https://bmclapi2.bangbang93.com/assets"),
+            libraries: String::from("https://bmclapi2.bangbang93.com/maven"),
+            fabric: String::from("https://bmclapi2.bangbang93.com/fabric-meta"),
+        }
+    }
+
+    fn get_base_url(&self, official_url: &str) -> &str {
+        match official_url {
+            "https://launchermeta.mojang.com"
+            | "https://launcher.mojang.com"
+            | "https://piston-meta.mojang.com"
+            | "https://piston-data.mojang.com" => &self.meta,
+            "https://resources.download.minecraft.net" => &self.assets,
+            "https://libraries.minecraft.net" | "https://maven.fabricmc.net" => &self.libraries,
+            "https://meta.fabricmc.net" => &self.fabric,
+            _ => "",
+        }
+    }
+}
+
+static MIRRORS: RwLock<Mirrors> = RwLock::new(Mirrors {
+    meta: String::new(),
+    assets: String::new(),
+    libraries: String::new(),
+    fabric: String::new(),
+});
+
+pub fn set_mirrors(mirrors: Mirrors) {
+    match MIRRORS.write() {
+        Ok(mut ok) => *ok = mirrors,
+        Err(e) => println!("Failed to set mirrors: {e}"),
+    }
+}
+
+// Points an official url to its mirror, other urls are kept.
+pub fn rewrite_url(url: &str) -> String {
+    let Ok(mirrors) = MIRRORS.read() else {
+        return url.to_owned();
+    };
+
+    // "https://libraries.minecraft.net/a/b.jar" -> ("https://libraries.minecraft.net", "/a/b.jar")
+    let host_end = match url.find("://") {
+        Some(scheme_end) => url[scheme_end + 3..]
+            .find('/')
+            .map_or(url.len(), |path_start| scheme_end + 3 + path_start),
+        None => return url.to_owned(),
+    };
+    let (official_url, path) = url.split_at(host_end);
+
+    match mirrors.get_base_url(official_url) {
+        "" => url.to_owned(),
+        base_url => format!("{}{}", base_url.trim_end_matches('/'), path),
+    }
+}
+
+// Sends the request to the mirror and to the official url if the mirror fails.
+pub async fn send<F: Fn(&str) -> RequestBuilder>(
+    url: &str,
+    request: F,
+) -> reqwest::Result<Response> {
+    let mirror_url = rewrite_url(url);
+
+    if mirror_url != url {
+        match request(&mirror_url).send().await {
+            Ok(ok) if ok.status().is_success() || ok.status().is_redirection() => return Ok(ok),
+            Ok(ok) => println!("Mirror failed for {}: {}", url, ok.status()),
+            Err(e) => println!("Mirror failed for {}: {}", url, e),
+        }
+    }
+
+    request(url).send().await
+}
@@ -7,7 +7,9 @@ use iced::{
     Alignment, Length,
 };
 
-use crate::{downloader, theme, widget::Renderer, LauncherState, Message, Screen};
+use crate::{
+    downloader, forge, launcher, network, theme, widget::Renderer, LauncherState, Message, Screen,
+};
 
 pub fn get_screen_content(
     minelander: &super::Minelander,
@@ -17,6 +19,7 @@ pub fn get_screen_content(
             let (launch_text, launch_message) = match minelander.launcher.state {
                 LauncherState::Idle => ("Launch", Option::Some(Message::Launch)),
                 LauncherState::Launching(_) => ("Launching", Option::None),
+                LauncherState::Attaching(_) => ("Attaching", Option::None),
                 LauncherState::GettingLogs => ("Running", Option::None),
                 LauncherState::Waiting => ("...", Option::None),
             };
@@ -139,98 +142,168 @@ pub fn get_screen_content(
             .max_width(800)
         }
 
-        Screen::Settings => column![
-            // Settings screen
-            //title
-            text("Settings").size(50),
-            //jvm and profile management
-            row![
-                container(
-                    column![
+        Screen::Settings => {
+            let memory_text = match (minelander.auto_ram, minelander.system_memory) {
+                (true, Some(memory)) => {
+                    let (max_memory, _) =
+                        launcher::get_automatic_memory(&memory, minelander.instance_mod_count);
+                    format!(
+                        "{:.1}GiB for {} mods, {:.1}GiB free of {:.1}GiB",
+                        max_memory as f64 / 1024.,
+                        minelander.instance_mod_count,
+                        memory.available as f64 / 1024.,
+                        memory.total as f64 / 1024.
+                    )
+                }
+                (false, Some(memory)) if minelander.game_ram * 1024. > memory.total as f64 => {
+                    format!(
+                        "More than the system memory ({:.1}GiB)!",
+                        memory.total as f64 / 1024.
+                    )
+                }
+                _ => String::new(),
+            };
+
+            let memory_title = match minelander.auto_ram {
+                true => String::from("Allocated memory: Automatic"),
+                false => format!("Allocated memory: {}GiB", minelander.game_ram),
+            };
+
+            column![
+                // Settings screen
+                //title
+                text("Settings").size(50),
+                //jvm and profile management
+                row![
+                    container(
                         column![
-                            text("JVM:"),
-                            pick_list(
-                                minelander.java_name_list.clone(),
-                                Some(minelander.current_java_name.clone()),
-                                Message::JavaChanged
-                            )
-                            .width(250)
-                            .text_size(25),
-                            button(
-                                text("Manage JVMs")
-                                    .width(250)
-                                    .horizontal_alignment(alignment::Horizontal::Center)
-                            )
-                            .height(32)
-                            .on_press(Message::ChangeScreen(Screen::Java))
+                            column![
+                                text("JVM:"),
+                                pick_list(
+                                    minelander.java_name_list.clone(),
+                                    Some(minelander.current_java_name.clone()),
+                                    Message::JavaChanged
+                                )
+                                .width(250)
+                                .text_size(25),
+                                button(
+                                    text("Manage JVMs")
+                                        .width(250)
+                                        .horizontal_alignment(alignment::Horizontal::Center)
+                                )
+                                .height(32)
+                                .on_press(Message::ChangeScreen(Screen::Java))
+                            ]
+                            .spacing(10)
+                            .max_width(800),
+                            column![
+                                text("Game instance:"),
+                                pick_list(
+                                    minelander.game_instance_list.clone(),
+                                    Some(minelander.current_game_instance.clone()),
+                                    Message::GameInstanceChanged
+                                )
+                                .width(250)
+                                .text_size(25),
+                                button(
+                                    text("Manage game instances")
+                                        .width(250)
+                                        .horizontal_alignment(alignment::Horizontal::Center)
+                                )
+                                .height(32)
+                                .on_press(Message::ChangeScreen(Screen::GameInstance)),
+                                button(
+                                    text("Java agents and properties")
+                                        .width(250)
+                                        .horizontal_alignment(alignment::Horizontal::Center)
+                                )
+                                .height(32)
+                                .on_press(Message::ChangeScreen(Screen::JavaAgents))
+                            ]
+                            .spacing(10)
+                            .max_width(800),
+                            column![
+                                text("JVM preset:"),
+                                pick_list(
+                                    [String::from("Automatic")]
+                                        .into_iter()
+                                        .chain(
+                                            minelander.jvm_presets.iter().map(|p| p.name.clone())
+                                        )
+                                        .collect::<Vec<_>>(),
+                                    Some(minelander.current_jvm_preset.clone()),
+                                    Message::JvmPresetChanged
+                                )
+                                .width(250)
+                                .text_size(15)
+                            ]
+                            .spacing(10)
+                            .max_width(800)
                         ]
                         .spacing(10)
-                        .max_width(800),
+                    )
+                    .style(theme::Container::BlackContainer)
+                    .padding(10),
+                    //memory, gamemode and showallversions option
+                    container(
                         column![
-                            text("Game instance:"),
-                            pick_list(
-                                minelander.game_instance_list.clone(),
-                                Some(minelander.current_game_instance.clone()),
-                                Message::GameInstanceChanged
-                            )
-                            .width(250)
-                            .text_size(25),
-                            button(
-                                text("Manage game instances")
+                            column![
+                                text(memory_title)
+                                    .size(25)
+                                    .horizontal_alignment(alignment::Horizontal::Center),
+                                slider(0.5..=16.0, minelander.game_ram, Message::GameRamChanged)
                                     .width(250)
-                                    .horizontal_alignment(alignment::Horizontal::Center)
-                            )
-                            .height(32)
-                            .on_press(Message::ChangeScreen(Screen::GameInstance))
+                                    .step(0.5),
+                                row![
+                                    toggler(
+                                        String::new(),
+                                        minelander.auto_ram,
+                                        Message::AutoRamChanged
+                                    )
+                                    .width(Length::Shrink),
+                                    text("Automatic memory")
+                                ]
+                                .spacing(10),
+                                text(memory_text).style(theme::Text::Peach).size(12)
+                            ]
+                            .spacing(5),
+                            button("Add wrapper commands")
+                                .on_press(Message::ChangeScreen(Screen::ModifyCommand))
                         ]
-                        .spacing(10)
-                        .max_width(800)
-                    ]
-                    .spacing(10)
-                )
-                .style(theme::Container::BlackContainer)
-                .padding(10),
-                //memory, gamemode and showallversions option
-                container(
-                    column![
-                        column![
-                            text(format!("Allocated memory: {}GiB", minelander.game_ram))
-                                .size(25)
-                                .horizontal_alignment(alignment::Horizontal::Center),
-                            slider(0.5..=16.0, minelander.game_ram, Message::GameRamChanged)
-                                .width(250)
-                                .step(0.5)
-                        ],
-                        button("Add wrapper commands")
-                            .on_press(Message::ChangeScreen(Screen::ModifyCommand))
-                    ]
-                    .spacing(50)
-                )
-                .style(theme::Container::BlackContainer)
-                .padding(10)
+                        .spacing(50)
+                    )
+                    .style(theme::Container::BlackContainer)
+                    .padding(10)
+                ]
+                .spacing(15),
             ]
-            .spacing(15),
-        ]
-        .spacing(15)
-        .max_width(800),
+            .spacing(15)
+            .max_width(800)
+        }
 
         Screen::Installation => {
+            let filter = minelander.version_filter;
+
             let vanilla_pick_list = pick_list(
-                minelander.vanilla_versions_download_list.clone(),
-                Some(minelander.vanilla_version_to_download.clone()),
+                minelander
+                    .version_filter
+                    .filter(&minelander.vanilla_versions_download_list, &minelander.version_search),
+                get_selected_version(&minelander.vanilla_versions_download_list, &minelander.vanilla_version_to_download),
                 Message::VanillaVersionToDownloadChanged,
             )
             .placeholder("Select a version")
-            .width(250)
+            .width(165)
             .text_size(15);
 
             let fabric_pick_list = pick_list(
-                minelander.fabric_versions_download_list.clone(),
-                Some(minelander.fabric_version_to_download.clone()),
+                minelander
+                    .version_filter
+                    .filter(&minelander.fabric_versions_download_list, &minelander.version_search),
+                get_selected_version(&minelander.fabric_versions_download_list, &minelander.fabric_version_to_download),
                 Message::FabricVersionToDownloadChanged,
             )
             .placeholder("Select a version")
-            .width(250)
+            .width(165)
             .text_size(15);
 
             let vanilla_button_message = match minelander.vanilla_version_to_download.is_empty() {
@@ -238,9 +311,97 @@ pub fn get_screen_content(
                 false => Some(Message::InstallVersion(downloader::VersionType::Vanilla)),
             };
 
-            let fabric_button_message = match minelander.fabric_version_to_download.is_empty() {
-                true => None,
-                false => Some(Message::InstallVersion(downloader::VersionType::Fabric)),
+            let fabric_loader_pick_list = pick_list(
+                minelander.fabric_loader_list.clone(),
+                minelander.fabric_loader_to_download.clone(),
+                Message::FabricLoaderToDownloadChanged,
+            )
+            .placeholder("Select a loader version")
+            .width(165)
+            .text_size(15);
+
+            let fabric_button_message = match &minelander.fabric_loader_to_download {
+                Some(loader) if !minelander.fabric_version_to_download.is_empty() => {
+                    let version_type = downloader::VersionType::Fabric(loader.version.clone());
+                    Some(Message::InstallVersion(version_type))
+                }
+                _ => None,
+            };
+
+            let quilt_pick_list = pick_list(
+                minelander
+                    .version_filter
+                    .filter(&minelander.quilt_versions_download_list, &minelander.version_search),
+                get_selected_version(&minelander.quilt_versions_download_list, &minelander.quilt_version_to_download),
+                Message::QuiltVersionToDownloadChanged,
+            )
+            .placeholder("Select a version")
+            .width(165)
+            .text_size(15);
+
+            let quilt_loader_pick_list = pick_list(
+                minelander.quilt_loader_list.clone(),
+                minelander.quilt_loader_to_download.clone(),
+                Message::QuiltLoaderToDownloadChanged,
+            )
+            .placeholder("Select a loader version")
+            .width(165)
+            .text_size(15);
+
+            let quilt_button_message = match &minelander.quilt_loader_to_download {
+                Some(loader) if !minelander.quilt_version_to_download.is_empty() => {
+                    let version_type = downloader::VersionType::Quilt(loader.version.clone());
+                    Some(Message::InstallVersion(version_type))
+                }
+                _ => None,
+            };
+
+            let forge_loader_pick_list = pick_list(
+                forge::ForgeLoader::ALL,
+                Some(minelander.forge_loader),
+                Message::ForgeLoaderChanged,
+            )
+            .width(165)
+            .text_size(15);
+
+            let forge_game_versions = match minelander.forge_loader {
+                forge::ForgeLoader::Forge => &minelander.forge_versions_download_list,
+                forge::ForgeLoader::NeoForge => &minelander.neoforge_versions_download_list,
+            };
+
+            let forge_pick_list = pick_list(
+                minelander
+                    .version_filter
+                    .filter(forge_game_versions, &minelander.version_search),
+                get_selected_version(forge_game_versions, &minelander.forge_version_to_download),
+                Message::ForgeVersionToDownloadChanged,
+            )
+            .placeholder("Select a version")
+            .width(165)
+            .text_size(15);
+
+            let forge_version_pick_list = pick_list(
+                minelander.forge_list.clone(),
+                minelander.forge_to_download.clone(),
+                Message::ForgeToDownloadChanged,
+            )
+            .placeholder("Select a loader version")
+            .width(165)
+            .text_size(15);
+
+            let forge_button_message = match &minelander.forge_to_download {
+                Some(forge) if !minelander.forge_version_to_download.is_empty() => {
+                    let version_type = match minelander.forge_loader {
+                        forge::ForgeLoader::Forge => {
+                            downloader::VersionType::Forge(forge.version.clone())
+                        }
+                        forge::ForgeLoader::NeoForge => {
+                            downloader::VersionType::NeoForge(forge.version.clone())
+                        }
+                    };
+                    Some(Message::InstallVersion(version_type))
+                }
+                _ => None,
             };
 
             let vanilla_install_button = button(
@@ -248,7 +409,7 @@ pub fn get_screen_content(
                     .size(20)
                     .horizontal_alignment(alignment::Horizontal::Center),
             )
-            .width(250)
+            .width(165)
             .height(40)
             .on_press_maybe(vanilla_button_message)
             .style(theme::Button::Secondary);
@@ -258,11 +419,31 @@ pub fn get_screen_content(
                     .size(20)
                     .horizontal_alignment(alignment::Horizontal::Center),
             )
-            .width(250)
+            .width(165)
             .height(40)
             .on_press_maybe(fabric_button_message)
             .style(theme::Button::Secondary);
 
+            let quilt_install_button = button(
+                text("Install")
+                    .size(20)
+                    .horizontal_alignment(alignment::Horizontal::Center),
+            )
+            .width(165)
+            .height(40)
+            .on_press_maybe(quilt_button_message)
+            .style(theme::Button::Secondary);
+
+            let forge_install_button = button(
+                text("Install")
+                    .size(20)
+                    .horizontal_alignment(alignment::Horizontal::Center),
+            )
+            .width(165)
+            .height(40)
+            .on_press_maybe(forge_button_message)
+            .style(theme::Button::Secondary);
+
             column![
                 //installerscreen
                 //title
@@ -285,26 +466,103 @@ pub fn get_screen_content(
                         column![
                             text("Fabric"),
                             fabric_pick_list,
+                            text("Loader:"),
+                            fabric_loader_pick_list,
                             //installbutton
                             fabric_install_button
                         ]
                         .spacing(15)
                     )
                     .style(theme::Container::BlackContainer)
+                    .padding(10),
+                    //quilt
+                    container(
+                        column![
+                            text("Quilt"),
+                            quilt_pick_list,
+                            text("Loader:"),
+                            quilt_loader_pick_list,
+                            //installbutton
+                            quilt_install_button
+                        ]
+                        .spacing(15)
+                    )
+                    .style(theme::Container::BlackContainer)
+                    .padding(10),
+                    //forge
+                    container(
+                        column![
+                            forge_loader_pick_list,
+                            forge_pick_list,
+                            text("Loader:"),
+                            forge_version_pick_list,
+                            //installbutton
+                            forge_install_button
+                        ]
+                        .spacing(15)
+                    )
+                    .style(theme::Container::BlackContainer)
                     .padding(10)
                 ]
                 .spacing(15),
                 row![
-                    toggler(
-                        String::new(),
-                        minelander.show_all_versions_in_download_list,
-                        Message::ShowAllVersionsInDownloadListChanged
-                    )
+                    text_input("Search versions", &minelander.version_search)
+                        .on_input(Message::VersionSearchChanged)
+                        .size(15)
+                        .width(200),
+                    toggler(String::new(), filter.release, move |release| {
+                        Message::VersionFilterChanged(downloader::VersionFilter {
+                            release,
+                            ..filter
+                        })
+                    })
                     .width(Length::Shrink),
-                    text("Show non-release versions")
-                        .horizontal_alignment(alignment::Horizontal::Center)
+                    text("Releases"),
+                    toggler(String::new(), filter.snapshot, move |snapshot| {
+                        Message::VersionFilterChanged(downloader::VersionFilter {
+                            snapshot,
+                            ..filter
+                        })
+                    })
+                    .width(Length::Shrink),
+                    text("Snapshots"),
+                    toggler(String::new(), filter.old_beta, move |old_beta| {
+                        Message::VersionFilterChanged(downloader::VersionFilter {
+                            old_beta,
+                            ..filter
+                        })
+                    })
+                    .width(Length::Shrink),
+                    text("Betas"),
+                    toggler(String::new(), filter.old_alpha, move |old_alpha| {
+                        Message::VersionFilterChanged(downloader::VersionFilter {
+                            old_alpha,
+                            ..filter
+                        })
+                    })
+                    .width(Length::Shrink),
+                    text("Alphas")
                 ]
-                .spacing(10),
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    text(format!(
+                        "Parallel downloads: {}",
+                        minelander.download_concurrency
+                    )),
+                    slider(
+                        1..=32,
+                        minelander.download_concurrency as u8,
+                        Message::DownloadConcurrencyChanged
+                    )
+                    .width(200),
+                    button("Network settings").on_press(Message::ChangeScreen(Screen::Network)),
+                    button("Installed versions")
+                        .style(theme::Button::Secondary)
+                        .on_press(Message::ChangeScreen(Screen::Versions))
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
                 text(&minelander.download_text).size(15)
             ]
             .spacing(15)
@@ -315,32 +573,234 @@ pub fn get_screen_content(
             text("Manage JVMs")
                 .size(50)
                 .horizontal_alignment(alignment::Horizontal::Center),
+            row![
+                container(
+                    column![
+                        text("New JVM"),
+                        text("JVM name:"),
+                        text_input("", &minelander.jvm_to_add_name)
+                            .on_input(Message::JvmNameToAddChanged)
+                            .size(25)
+                            .width(250),
+                        text("JVM path:"),
+                        text_input("", &minelander.jvm_to_add_path)
+                            .on_input(Message::JvmPathToAddChanged)
+                            .size(25)
+                            .width(250),
+                        text("JVM flags:"),
+                        text_input("", &minelander.jvm_to_add_flags)
+                            .on_input(Message::JvmFlagsToAddChanged)
+                            .size(25)
+                            .width(250),
+                        button(
+                            text("Add")
+                                .size(15)
+                                .horizontal_alignment(alignment::Horizontal::Center)
+                        )
+                        .width(135)
+                        .height(35)
+                        .on_press(Message::JvmAdded)
+                    ]
+                    .spacing(5)
+                )
+                .style(theme::Container::BlackContainer)
+                .padding(15),
+                container(
+                    column![
+                        text("Install Java"),
+                        text("Java version:"),
+                        text_input("e.g. 17", &minelander.java_to_install)
+                            .on_input(Message::JavaToInstallChanged)
+                            .size(25)
+                            .width(250),
+                        button(
+                            text("Install")
+                                .size(15)
+                                .horizontal_alignment(alignment::Horizontal::Center)
+                        )
+                        .width(135)
+                        .height(35)
+                        .on_press(Message::InstallJava),
+                        text(&minelander.game_state_text).size(15),
+                        button("Manage installed Java")
+                            .on_press(Message::ChangeScreen(Screen::JavaRuntimes)),
+                        button("JVM presets").on_press(Message::ChangeScreen(Screen::JvmPresets))
+                    ]
+                    .spacing(5)
+                )
+                .style(theme::Container::BlackContainer)
+                .padding(15)
+            ]
+            .spacing(15)
+        ]
+        .spacing(15)
+        .max_width(800),
+        Screen::JavaRuntimes => {
+            let mut runtimes_column = column![].spacing(10);
+            for runtime in &minelander.java_runtimes {
+                let mut used_by = vec![];
+                if runtime.current {
+                    used_by.push(String::from("Current runtime"));
+                }
+                if !runtime.automatic_for.is_empty() {
+                    used_by.push(format!(
+                        "Automatic for: {}",
+                        runtime.automatic_for.join(", ")
+                    ));
+                }
+                if used_by.is_empty() {
+                    used_by.push(String::from("Not in use"));
+                }
+
+                runtimes_column = runtimes_column.push(
+                    row![
+                        column![
+                            text(format!(
+                                "{} ({}, {} MiB)",
+                                runtime.name,
+                                runtime.version,
+                                runtime.size / 1048576
+                            )),
+                            text(used_by.join("\n")).size(12)
+                        ]
+                        .width(Length::Fill),
+                        button("Update").on_press(Message::UpdateJavaRuntime(runtime.name.clone())),
+                        button("Reinstall")
+                            .style(theme::Button::Secondary)
+                            .on_press(Message::ReinstallJavaRuntime(runtime.name.clone())),
+                        button("Uninstall")
+                            .style(theme::Button::Red)
+                            .on_press(Message::UninstallJavaRuntime(runtime.name.clone()))
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                );
+            }
+            if minelander.java_runtimes.is_empty() {
+                runtimes_column =
+                    runtimes_column.push(text("No Java was installed by Minelander."));
+            }
+
+            column![
+                text("Installed Java").size(50),
+                container(scrollable(runtimes_column).height(300))
+                    .style(theme::Container::BlackContainer)
+                    .padding(15),
+                text(&minelander.java_runtimes_text).size(15),
+                text(&minelander.game_state_text).size(15),
+                text(&minelander.download_text).size(15)
+            ]
+            .spacing(15)
+            .max_width(800)
+        }
+        Screen::Versions => {
+            let mut versions_column = column![].spacing(10);
+            for version in &minelander.all_versions {
+                let buttons = match minelander.version_to_uninstall.as_ref() == Some(version) {
+                    true => row![
+                        button("Cancel")
+                            .style(theme::Button::Secondary)
+                            .on_press(Message::UninstallVersionCancelled),
+                        button("Confirm uninstall")
+                            .style(theme::Button::Red)
+                            .on_press(Message::UninstallVersionConfirmed)
+                    ],
+                    false => row![
+                        button("Verify and repair")
+                            .on_press(Message::RepairVersion(version.clone())),
+                        button("Uninstall")
+                            .style(theme::Button::Red)
+                            .on_press(Message::UninstallVersion(version.clone()))
+                    ],
+                };
+                versions_column = versions_column.push(
+                    row![text(version).width(Length::Fill), buttons.spacing(10)]
+                        .spacing(10)
+                        .align_items(Alignment::Center),
+                );
+            }
+            if minelander.all_versions.is_empty() {
+                versions_column = versions_column.push(text("No versions installed."));
+            }
+
+            column![
+                text("Installed versions").size(50),
+                container(scrollable(versions_column).height(250))
+                    .style(theme::Container::BlackContainer)
+                    .padding(15),
+                container(
+                    row![
+                        text("Clean up libraries and assets that no installed version uses.")
+                            .width(Length::Fill),
+                        button("Find unused files").on_press(Message::FindUnusedFiles),
+                        button("Delete unused files")
+                            .style(theme::Button::Red)
+                            .on_press_maybe(
+                                minelander
+                                    .unused_files
+                                    .as_ref()
+                                    .filter(|unused_files| !unused_files.files.is_empty())
+                                    .map(|_| Message::DeleteUnusedFiles)
+                            )
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                )
+                .style(theme::Container::BlackContainer)
+                .padding(15),
+                text(&minelander.versions_text).size(15)
+            ]
+            .spacing(15)
+            .max_width(800)
+        }
+        Screen::JvmPresets => column![
+            text("JVM presets").size(50),
             container(
                 column![
-                    text("New JVM"),
-                    text("JVM name:"),
-                    text_input("", &minelander.jvm_to_add_name)
-                        .on_input(Message::JvmNameToAddChanged)
-                        .size(25)
-                        .width(250),
-                    text("JVM path:"),
-                    text_input("", &minelander.jvm_to_add_path)
-                        .on_input(Message::JvmPathToAddChanged)
-                        .size(25)
-                        .width(250),
-                    text("JVM flags:"),
-                    text_input("", &minelander.jvm_to_add_flags)
-                        .on_input(Message::JvmFlagsToAddChanged)
-                        .size(25)
-                        .width(250),
-                    button(
-                        text("Add")
-                            .size(15)
-                            .horizontal_alignment(alignment::Horizontal::Center)
+                    pick_list(
+                        minelander
+                            .jvm_presets
+                            .iter()
+                            .map(|preset| preset.name.clone())
+                            .collect::<Vec<_>>(),
+                        None::<String>,
+                        Message::JvmPresetToEditChanged
                     )
-                    .width(135)
-                    .height(35)
-                    .on_press(Message::JvmAdded)
+                    .placeholder("Edit a preset")
+                    .width(250)
+                    .text_size(15),
+                    text("Name:"),
+                    text_input("", &minelander.jvm_preset_name)
+                        .on_input(Message::JvmPresetNameChanged)
+                        .width(250),
+                    text("JVM arguments:"),
+                    text_input("", &minelander.jvm_preset_args)
+                        .on_input(Message::JvmPresetArgsChanged),
+                    row![
+                        column![
+                            text("Minimum Java:"),
+                            text_input("0", &minelander.jvm_preset_min_java)
+                                .on_input(Message::JvmPresetMinJavaChanged)
+                                .width(120)
+                        ]
+                        .spacing(5),
+                        column![
+                            text("Maximum Java:"),
+                            text_input("0 = none", &minelander.jvm_preset_max_java)
+                                .on_input(Message::JvmPresetMaxJavaChanged)
+                                .width(120)
+                        ]
+                        .spacing(5)
+                    ]
+                    .spacing(10),
+                    row![
+                        button("Save").on_press(Message::JvmPresetSaved),
+                        button("Delete")
+                            .style(theme::Button::Red)
+                            .on_press(Message::JvmPresetDeleted)
+                    ]
+                    .spacing(10),
+                    text(&minelander.jvm_presets_text).size(15)
                 ]
                 .spacing(5)
             )
@@ -349,6 +809,269 @@ pub fn get_screen_content(
         ]
         .spacing(15)
         .max_width(800),
+        Screen::JavaAgents => {
+            let mut agents_column = column![text("Java agents")].spacing(5);
+            for (index, agent) in minelander.java_agents.iter().enumerate() {
+                let agent_text = match agent.options.is_empty() {
+                    true => agent.path.clone(),
+                    false => format!("{}={}", agent.path, agent.options),
+                };
+                agents_column = agents_column.push(
+                    row![
+                        text(agent_text).size(12).width(Length::Fill),
+                        button("Remove")
+                            .style(theme::Button::Red)
+                            .on_press(Message::JavaAgentRemoved(index))
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                );
+            }
+            agents_column = agents_column.push(
+                row![
+                    text_input("Absolute agent jar path", &minelander.java_agent_to_add.path)
+                        .on_input(Message::JavaAgentPathChanged),
+                    text_input("Options", &minelander.java_agent_to_add.options)
+                        .on_input(Message::JavaAgentOptionsChanged)
+                        .width(150),
+                    button("Add").on_press(Message::JavaAgentAdded)
+                ]
+                .spacing(10),
+            );
+
+            let mut properties_column = column![text("System properties")].spacing(5);
+            for (index, property) in minelander.system_properties.iter().enumerate() {
+                properties_column = properties_column.push(
+                    row![
+                        text(format!("-D{}={}", property.key, property.value))
+                            .size(12)
+                            .width(Length::Fill),
+                        button("Remove")
+                            .style(theme::Button::Red)
+                            .on_press(Message::SystemPropertyRemoved(index))
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                );
+            }
+            properties_column = properties_column.push(
+                row![
+                    text_input("Name", &minelander.system_property_to_add.key)
+                        .on_input(Message::SystemPropertyKeyChanged),
+                    text_input("Value", &minelander.system_property_to_add.value)
+                        .on_input(Message::SystemPropertyValueChanged),
+                    button("Add").on_press(Message::SystemPropertyAdded)
+                ]
+                .spacing(10),
+            );
+
+            column![
+                text(format!(
+                    "Java agents and properties ({})",
+                    minelander.current_game_instance
+                ))
+                .size(35),
+                container(
+                    scrollable(column![agents_column, properties_column].spacing(20)).height(300)
+                )
+                .style(theme::Container::BlackContainer)
+                .padding(15),
+                text(&minelander.java_agents_text).size(15)
+            ]
+            .spacing(15)
+            .max_width(800)
+        }
+        Screen::Network => {
+            let mirrors = &minelander.mirrors;
+
+            let meta_mirror = mirrors.clone();
+            let assets_mirror = mirrors.clone();
+            let libraries_mirror = mirrors.clone();
+            let fabric_mirror = mirrors.clone();
+
+            let network_settings = &minelander.network_settings;
+
+            let proxy_settings = network_settings.clone();
+            let username_settings = network_settings.clone();
+            let password_settings = network_settings.clone();
+            let user_agent_settings = network_settings.clone();
+            let connect_timeout_settings = network_settings.clone();
+            let timeout_settings = network_settings.clone();
+
+            // 0 disables the timeout
+            let get_timeout_text = |timeout: u16| match timeout {
+                0 => String::from("none"),
+                timeout => format!("{}s", timeout),
+            };
+
+            column![
+                text("Network").size(50),
+                row![
+                    container(
+                        column![
+                            text("Mirrors"),
+                            text("Empty fields use the official servers, which are also used when a mirror fails.")
+                                .size(12),
+                            row![
+                                text("Mojang metadata").width(120),
+                                text_input("https://launchermeta.mojang.com", &mirrors.meta)
+                                    .on_input(move |meta| Message::MirrorsChanged(network::Mirrors {
+                                        meta,
+                                        ..meta_mirror.clone()
+                                    }))
+                                    .on_submit(Message::MirrorsApplied)
+                            ]
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            row![
+                                text("Assets").width(120),
+                                text_input("https://resources.download.minecraft.net", &mirrors.assets)
+                                    .on_input(move |assets| Message::MirrorsChanged(network::Mirrors {
+                                        assets,
+                                        ..assets_mirror.clone()
+                                    }))
+                                    .on_submit(Message::MirrorsApplied)
+                            ]
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            row![
+                                text("Libraries").width(120),
+                                text_input("https://libraries.minecraft.net", &mirrors.libraries)
+                                    .on_input(move |libraries| Message::MirrorsChanged(
+                                        network::Mirrors {
+                                            libraries,
+                                            ..libraries_mirror.clone()
+                                        }
+                                    ))
+                                    .on_submit(Message::MirrorsApplied)
+                            ]
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            row![
+                                text("Fabric metadata").width(120),
+                                text_input("https://meta.fabricmc.net", &mirrors.fabric)
+                                    .on_input(move |fabric| Message::MirrorsChanged(network::Mirrors {
+                                        fabric,
+                                        ..fabric_mirror.clone()
+                                    }))
+                                    .on_submit(Message::MirrorsApplied)
+                            ]
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            row![
+                                button("Apply").on_press(Message::MirrorsApplied),
+                                button("Use BMCLAPI")
+                                    .on_press(Message::MirrorsChanged(network::Mirrors::bmclapi())),
+                                button("Use official servers")
+                                    .style(theme::Button::Secondary)
+                                    .on_press(Message::MirrorsChanged(network::Mirrors::default()))
+                            ]
+                            .spacing(10)
+                        ]
+                        .spacing(15)
+                    )
+                    .style(theme::Container::BlackContainer)
+                    .padding(15)
+                    .width(445),
+                    container(
+                        column![
+                            text("Proxy"),
+                            text("When empty, the HTTP_PROXY, HTTPS_PROXY and ALL_PROXY variables are used.")
+                                .size(12),
+                            text_input("http://, https:// or socks5://host:port", &network_settings.proxy)
+                                .on_input(move |proxy| Message::NetworkSettingsChanged(
+                                    network::NetworkSettings {
+                                        proxy,
+                                        ..proxy_settings.clone()
+                                    }
+                                ))
+                                .on_submit(Message::NetworkSettingsApplied),
+                            row![
+                                text_input("Username", &network_settings.proxy_username)
+                                    .on_input(move |proxy_username| Message::NetworkSettingsChanged(
+                                        network::NetworkSettings {
+                                            proxy_username,
+                                            ..username_settings.clone()
+                                        }
+                                    ))
+                                    .on_submit(Message::NetworkSettingsApplied),
+                                text_input("Password", &network_settings.proxy_password)
+                                    .secure(true)
+                                    .on_input(move |proxy_password| Message::NetworkSettingsChanged(
+                                        network::NetworkSettings {
+                                            proxy_password,
+                                            ..password_settings.clone()
+                                        }
+                                    ))
+                                    .on_submit(Message::NetworkSettingsApplied)
+                            ]
+                            .spacing(10),
+                            row![
+                                text("User agent").width(120),
+                                text_input(&network_settings.get_user_agent(), &network_settings.user_agent)
+                                    .on_input(move |user_agent| Message::NetworkSettingsChanged(
+                                        network::NetworkSettings {
+                                            user_agent,
+                                            ..user_agent_settings.clone()
+                                        }
+                                    ))
+                                    .on_submit(Message::NetworkSettingsApplied)
+                            ]
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            row![
+                                text(format!(
+                                    "Connect timeout: {}",
+                                    get_timeout_text(network_settings.connect_timeout)
+                                ))
+                                .width(170),
+                                slider(0..=60, network_settings.connect_timeout, move |connect_timeout| {
+                                    Message::NetworkSettingsChanged(network::NetworkSettings {
+                                        connect_timeout,
+                                        ..connect_timeout_settings.clone()
+                                    })
+                                })
+                                .on_release(Message::NetworkSettingsApplied)
+                            ]
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            row![
+                                text(format!(
+                                    "Request timeout: {}",
+                                    get_timeout_text(network_settings.timeout)
+                                ))
+                                .width(170),
+                                slider(0..=600, network_settings.timeout, move |timeout| {
+                                    Message::NetworkSettingsChanged(network::NetworkSettings {
+                                        timeout,
+                                        ..timeout_settings.clone()
+                                    })
+                                })
+                                .step(10u16)
+                                .on_release(Message::NetworkSettingsApplied)
+                            ]
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            row![
+                                button("Apply").on_press(Message::NetworkSettingsApplied),
+                                text(&minelander.network_text)
+                                    .size(15)
+                                    .style(theme::Text::Red)
+                            ]
+                            .spacing(10)
+                            .align_items(Alignment::Center)
+                        ]
+                        .spacing(15)
+                    )
+                    .style(theme::Container::BlackContainer)
+                    .padding(15)
+                    .width(340)
+                ]
+                .spacing(15)
+            ]
+            .spacing(15)
+            .max_width(800)
+        }
         Screen::GameInstance => column![
             text("Manage game instances")
                 .size(50)
@@ -378,17 +1101,25 @@ pub fn get_screen_content(
         .spacing(15)
         .max_width(800),
 
-        Screen::Logs => column![
-            text("Game logs").size(25),
-            container(
-                scrollable(text(minelander.logs.join("\n")).size(10))
-                    .width(700.0)
-                    .height(345.)
-            )
-            .style(theme::Container::BlackContainer)
-            .padding(5)
-        ]
-        .spacing(10),
+        Screen::Logs => {
+            let log_file_text = match minelander.game_log_file.is_empty() {
+                true => None,
+                false => Some(text(format!("Log file: {}", minelander.game_log_file)).size(12)),
+            };
+
+            column![
+                text("Game logs").size(25),
+                container(
+                    scrollable(text(minelander.logs.join("\n")).size(10))
+                        .width(700.0)
+                        .height(345.)
+                )
+                .style(theme::Container::BlackContainer)
+                .padding(5)
+            ]
+            .push_maybe(log_file_text)
+            .spacing(10)
+        }
         Screen::ModifyCommand => column![
             text("Modify game command").size(50),
             text("Wraper commands").size(25),
@@ -404,7 +1135,31 @@ pub fn get_screen_content(
                 &minelander.game_enviroment_variables
             )
             .on_input(Message::GameEnviromentVariablesChanged)
-            .size(12)
+            .size(12),
+            row![
+                toggler(
+                    String::new(),
+                    minelander.detach_game,
+                    Message::DetachGameChanged
+                )
+                .width(Length::Shrink),
+                text("Launch detached (game keeps running after closing Minelander, logs are written to a file)")
+                    .size(15)
+            ]
+            .spacing(10),
+            row![
+                button("Export launch script").on_press(Message::ExportLaunchScript),
+                toggler(
+                    String::new(),
+                    minelander.redact_launch_script_token,
+                    Message::RedactLaunchScriptTokenChanged
+                )
+                .width(Length::Shrink),
+                text("Redact access token").size(15)
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center),
+            text(&minelander.launch_script_text).size(12)
         ]
         .spacing(25),
         Screen::InfoAndUpdates => {
@@ -578,5 +1333,40 @@ pub fn get_screen_content(
             .style(theme::Container::BlackContainer)
             .padding(15)
         ].spacing(25),
+        Screen::ExitPrompt => {
+            let keep_running_text = match minelander.game_log_file.is_empty() {
+                true => "The game is still running. If you keep it running, its logs will no longer be captured.",
+                false => "The game is still running. If you keep it running, its logs will still be written to the log file and Minelander will attach to it when reopened.",
+            };
+
+            column![
+                text("Exit Minelander").size(50),
+                container(
+                    column![
+                        text(keep_running_text),
+                        row![
+                            button("Keep game running").on_press(Message::ExitKeepingGame),
+                            button("Stop game")
+                                .on_press(Message::ExitClosingGame)
+                                .style(theme::Button::Red),
+                            button("Cancel").on_press(Message::ChangeScreen(Screen::Main)),
+                        ]
+                        .spacing(10),
+                    ]
+                    .spacing(15)
+                )
+                .style(theme::Container::BlackContainer)
+                .padding(15)
+            ]
+            .spacing(25)
+        }
     }
 }
+
+// The selected version stays shown when the filters hide it.
+fn get_selected_version(
+    versions: &[downloader::ManifestVersion],
+    id: &str,
+) -> Option<downloader::ManifestVersion> {
+    versions.iter().find(|version| version.id == id).cloned()
+}
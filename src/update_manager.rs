@@ -1,12 +1,10 @@
 use std::env;
 
-use reqwest::header::{HeaderValue, USER_AGENT};
 use serde_json::Value;
 
 pub async fn check_launcher_updates() -> Result<(String, String), String> {
-    let last_release_request = match reqwest::Client::new()
+    let last_release_request = match super::network::client()
         .get("https://api.github.com/repos/jafkc2/minelander/releases/latest")
-        .header(USER_AGENT, HeaderValue::from_static("minelander"))
         .send()
         .await
     {